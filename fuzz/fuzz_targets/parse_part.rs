@@ -0,0 +1,68 @@
+//! Feed arbitrary text through every parser and joiner, none of them may panic
+//!
+//! Run with `cargo +nightly fuzz run parse_part` from the repository root
+
+#![no_main]
+
+use bbqr::{
+    continuous_join::ContinuousJoiner,
+    decode::DecodeLimits,
+    fixed_join::FixedJoiner,
+    header::Header,
+    join::{JoinMode, Joined},
+    part::Part,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let lines: Vec<&str> = input.lines().collect();
+
+    let parsed: Vec<Part> = lines
+        .iter()
+        .filter_map(|line| {
+            let _ = Header::try_from_str(line);
+            Part::parse_with_mode(line, JoinMode::Lenient).ok()
+        })
+        .collect();
+
+    for part in &parsed {
+        let displayed = part.to_string();
+        assert_eq!(Part::parse_with_mode(&displayed, JoinMode::Lenient).as_ref(), Ok(part));
+    }
+
+    let _ = Joined::try_from_parts(lines.iter().map(|line| line.to_string()).collect());
+    let _ = Joined::try_from_parsed_parts(&parsed);
+
+    let mut joiner = ContinuousJoiner::with_mode(JoinMode::Lenient);
+    for line in &lines {
+        let _ = joiner.add_part(line.to_string());
+    }
+
+    let mut joiner = ContinuousJoiner::new();
+    for part in &parsed {
+        let _ = joiner.add_parsed_part(*part);
+    }
+
+    // small limits, set from the input so they vary, make the size checks fail part way
+    // through a join, every line is added twice so parts are re-added after the error
+    let limits = DecodeLimits {
+        max_decoded_size: Some(input.len() % 64),
+        max_ratio: Some(input.len() % 4 + 1),
+    };
+
+    let mut joiner = ContinuousJoiner::with_limits(limits);
+    for line in lines.iter().chain(&lines) {
+        let _ = joiner.add_part(line.to_string());
+    }
+
+    let mut joiner = ContinuousJoiner::with_options(JoinMode::Lenient, limits);
+    for part in parsed.iter().chain(&parsed) {
+        let _ = joiner.add_parsed_part(*part);
+    }
+
+    let mut buffer = [0; 4096];
+    let mut joiner = FixedJoiner::new(&mut buffer);
+    for line in &lines {
+        let _ = joiner.add_part(line);
+    }
+});
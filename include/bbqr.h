@@ -0,0 +1,251 @@
+#ifndef BBQR_H
+#define BBQR_H
+
+/* Generated with cbindgen, do not edit by hand, see cbindgen.toml */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Numeric error codes, mirroring `SplitError`, `JoinError` and `ContinuousJoinError`
+enum BbqrErrorCode
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  BBQR_ERROR_CODE_OK = 0,
+  BBQR_ERROR_CODE_NULL_POINTER = 1,
+  BBQR_ERROR_CODE_INVALID_UTF8 = 2,
+  BBQR_ERROR_CODE_INVALID_ENCODING = 3,
+  BBQR_ERROR_CODE_INVALID_FILE_TYPE = 4,
+  BBQR_ERROR_CODE_INVALID_VERSION = 5,
+  BBQR_ERROR_CODE_NOT_COMPLETE = 6,
+  BBQR_ERROR_CODE_PANIC = 7,
+  BBQR_ERROR_CODE_INVALID_ERROR_CORRECTION = 8,
+  BBQR_ERROR_CODE_SPLIT_EMPTY = 10,
+  BBQR_ERROR_CODE_SPLIT_CANNOT_FIT = 11,
+  BBQR_ERROR_CODE_SPLIT_MAX_SPLIT_SIZE_TOO_LARGE = 12,
+  BBQR_ERROR_CODE_SPLIT_MIN_SPLIT_TOO_SMALL = 13,
+  BBQR_ERROR_CODE_SPLIT_INVALID_SPLIT_RANGE = 14,
+  BBQR_ERROR_CODE_SPLIT_INVALID_VERSION_RANGE = 15,
+  BBQR_ERROR_CODE_SPLIT_READ_ERROR = 16,
+  BBQR_ERROR_CODE_ENCODE_EMPTY = 20,
+  BBQR_ERROR_CODE_ENCODE_COMPRESSION_ERROR = 21,
+  BBQR_ERROR_CODE_JOIN_EMPTY = 30,
+  BBQR_ERROR_CODE_JOIN_CONFLICTING_HEADERS = 31,
+  BBQR_ERROR_CODE_JOIN_TOO_MANY_PARTS = 32,
+  BBQR_ERROR_CODE_JOIN_DUPLICATE_PART_WRONG_CONTENT = 33,
+  BBQR_ERROR_CODE_JOIN_PART_WITH_NO_DATA = 34,
+  BBQR_ERROR_CODE_JOIN_MISSING_PART = 35,
+  BBQR_ERROR_CODE_JOIN_WRITE_ERROR = 36,
+  BBQR_ERROR_CODE_HEADER_EMPTY = 40,
+  BBQR_ERROR_CODE_HEADER_INVALID_ENCODING = 41,
+  BBQR_ERROR_CODE_HEADER_INVALID_FILE_TYPE = 42,
+  BBQR_ERROR_CODE_HEADER_INVALID_FIXED_HEADER = 43,
+  BBQR_ERROR_CODE_HEADER_INVALID_HEADER_SIZE = 44,
+  BBQR_ERROR_CODE_HEADER_INVALID_HEADER_PARTS = 45,
+  BBQR_ERROR_CODE_HEADER_INVALID_PART_INDEX = 46,
+  BBQR_ERROR_CODE_HEADER_NOT_ASCII = 47,
+  BBQR_ERROR_CODE_DECODE_UNABLE_TO_DECODE_HEX = 50,
+  BBQR_ERROR_CODE_DECODE_UNABLE_TO_DECODE_BASE32 = 51,
+  BBQR_ERROR_CODE_DECODE_UNABLE_TO_INFLATE_ZLIB = 52,
+  BBQR_ERROR_CODE_DECODE_INVALID_PART_LENGTH = 53,
+  BBQR_ERROR_CODE_DECODE_SIZE_LIMIT_EXCEEDED = 54,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum BbqrErrorCode BbqrErrorCode;
+#else
+typedef int32_t BbqrErrorCode;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+// The status of a continuous join, mirrors `ContinuousJoinResult`
+enum BbqrJoinStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  BBQR_JOIN_STATUS_NOT_STARTED = 0,
+  BBQR_JOIN_STATUS_IN_PROGRESS = 1,
+  BBQR_JOIN_STATUS_COMPLETE = 2,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum BbqrJoinStatus BbqrJoinStatus;
+#else
+typedef int32_t BbqrJoinStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+// Opaque handle to a continuous joiner
+typedef struct BbqrContinuousJoiner BbqrContinuousJoiner;
+
+// Opaque handle to the result of a split
+typedef struct BbqrSplit BbqrSplit;
+
+// Options for [`bbqr_split_from_data`], initialise with [`bbqr_split_options_default`]
+typedef struct BbqrSplitOptions {
+  // The encoding header byte, `'H'`, `'2'` or `'Z'`
+  uint8_t encoding;
+  // The minimum number of parts to split to
+  size_t min_split_number;
+  // The maximum number of parts to split to
+  size_t max_split_number;
+  // The minimum QR version number, 1 to 40
+  uint8_t min_version;
+  // The maximum QR version number, 1 to 40
+  uint8_t max_version;
+  // Spread the data evenly over the parts, instead of leaving a small last part
+  bool balanced;
+  // The QR error correction level, `'L'`, `'M'`, `'Q'` or `'H'`
+  uint8_t error_correction;
+} BbqrSplitOptions;
+
+// Joined data, `data` is owned by the caller and must be released with [`bbqr_joined_free`]
+typedef struct BbqrJoined {
+  // The encoding header byte, `'H'`, `'2'` or `'Z'`
+  uint8_t encoding;
+  // The file type header byte, ex: `'P'` for PSBT
+  uint8_t file_type;
+  uint8_t *data;
+  size_t len;
+} BbqrJoined;
+
+// The progress of a continuous join after adding a part
+typedef struct BbqrJoinProgress {
+  BbqrJoinStatus status;
+  // The number of parts left to join, only meaningful when the status is `InProgress`
+  size_t parts_left;
+} BbqrJoinProgress;
+
+#define QR_DATA_CAPACITY { { { 152, 41, 25, 17, 10, }, { 128, 34, 20, 14, 8, }, { 104, 27, 16, 11, 7, }, { 72, 17, 10, 7, 4, }, }, { { 272, 77, 47, 32, 20, }, { 224, 63, 38, 26, 16, }, { 176, 48, 29, 20, 12, }, { 128, 34, 20, 14, 8, }, }, { { 440, 127, 77, 53, 32, }, { 352, 101, 61, 42, 26, }, { 272, 77, 47, 32, 20, }, { 208, 58, 35, 24, 15, }, }, { { 640, 187, 114, 78, 48, }, { 512, 149, 90, 62, 38, }, { 384, 111, 67, 46, 28, }, { 288, 82, 50, 34, 21, }, }, { { 864, 255, 154, 106, 65, }, { 688, 202, 122, 84, 52, }, { 496, 144, 87, 60, 37, }, { 368, 106, 64, 44, 27, }, }, { { 1088, 322, 195, 134, 82, }, { 864, 255, 154, 106, 65, }, { 608, 178, 108, 74, 45, }, { 480, 139, 84, 58, 36, }, }, { { 1248, 370, 224, 154, 95, }, { 992, 293, 178, 122, 75, }, { 704, 207, 125, 86, 53, }, { 528, 154, 93, 64, 39, }, }, { { 1552, 461, 279, 192, 118, }, { 1232, 365, 221, 152, 93, }, { 880, 259, 157, 108, 66, }, { 688, 202, 122, 84, 52, }, }, { { 1856, 552, 335, 230, 141, }, { 1456, 432, 262, 180, 111, }, { 1056, 312, 189, 130, 80, }, { 800, 235, 143, 98, 60, }, }, { { 2192, 652, 395, 271, 167, }, { 1728, 513, 311, 213, 131, }, { 1232, 364, 221, 151, 93, }, { 976, 288, 174, 119, 74, }, }, { { 2592, 772, 468, 321, 198, }, { 2032, 604, 366, 251, 155, }, { 1440, 427, 259, 177, 109, }, { 1120, 331, 200, 137, 85, }, }, { { 2960, 883, 535, 367, 226, }, { 2320, 691, 419, 287, 177, }, { 1648, 489, 296, 203, 125, }, { 1264, 374, 227, 155, 96, }, }, { { 3424, 1022, 619, 425, 262, }, { 2672, 796, 483, 331, 204, }, { 1952, 580, 352, 241, 149, }, { 1440, 427, 259, 177, 109, }, }, { { 3688, 1101, 667, 458, 282, }, { 2920, 871, 528, 362, 223, }, { 2088, 621, 376, 258, 159, }, { 1576, 468, 283, 194, 120, }, }, { { 4184, 1250, 758, 520, 320, }, { 3320, 991, 600, 412, 254, }, { 2360, 703, 426, 292, 180, }, { 1784, 530, 321, 220, 136, }, }, { { 4712, 1408, 854, 586, 361, }, { 3624, 1082, 656, 450, 277, }, { 2600, 775, 470, 322, 198, }, { 2024, 602, 365, 250, 154, }, }, { { 5176, 1548, 938, 644, 397, }, { 4056, 1212, 734, 504, 310, }, { 2936, 876, 531, 364, 224, }, { 2264, 674, 408, 280, 173, }, }, { { 5768, 1725, 1046, 718, 442, }, { 4504, 1346, 816, 560, 345, }, { 3176, 948, 574, 394, 243, }, { 2504, 746, 452, 310, 191, }, }, { { 6360, 1903, 1153, 792, 488, }, { 5016, 1500, 909, 624, 384, }, { 3560, 1063, 644, 442, 272, }, { 2728, 813, 493, 338, 208, }, }, { { 6888, 2061, 1249, 858, 528, }, { 5352, 1600, 970, 666, 410, }, { 3880, 1159, 702, 482, 297, }, { 3080, 919, 557, 382, 235, }, }, { { 7456, 2232, 1352, 929, 572, }, { 5712, 1708, 1035, 711, 438, }, { 4096, 1224, 742, 509, 314, }, { 3248, 969, 587, 403, 248, }, }, { { 8048, 2409, 1460, 1003, 618, }, { 6256, 1872, 1134, 779, 480, }, { 4544, 1358, 823, 565, 348, }, { 3536, 1056, 640, 439, 270, }, }, { { 8752, 2620, 1588, 1091, 672, }, { 6880, 2059, 1248, 857, 528, }, { 4912, 1468, 890, 611, 376, }, { 3712, 1108, 672, 461, 284, }, }, { { 9392, 2812, 1704, 1171, 721, }, { 7312, 2188, 1326, 911, 561, }, { 5312, 1588, 963, 661, 407, }, { 4112, 1228, 744, 511, 315, }, }, { { 10208, 3057, 1853, 1273, 784, }, { 8000, 2395, 1451, 997, 614, }, { 5744, 1718, 1041, 715, 440, }, { 4304, 1286, 779, 535, 330, }, }, { { 10960, 3283, 1990, 1367, 842, }, { 8496, 2544, 1542, 1059, 652, }, { 6032, 1804, 1094, 751, 462, }, { 4768, 1425, 864, 593, 365, }, }, { { 11744, 3514, 2132, 1465, 902, }, { 9024, 2701, 1637, 1125, 692, }, { 6464, 1933, 1172, 805, 496, }, { 5024, 1501, 910, 625, 385, }, }, { { 12248, 3669, 2223, 1528, 940, }, { 9544, 2857, 1732, 1190, 732, }, { 6968, 2085, 1263, 868, 534, }, { 5288, 1581, 958, 658, 405, }, }, { { 13048, 3909, 2369, 1628, 1002, }, { 10136, 3035, 1839, 1264, 778, }, { 7288, 2181, 1322, 908, 559, }, { 5608, 1677, 1016, 698, 430, }, }, { { 13880, 4158, 2520, 1732, 1066, }, { 10984, 3289, 1994, 1370, 843, }, { 7880, 2358, 1429, 982, 604, }, { 5960, 1782, 1080, 742, 457, }, }, { { 14744, 4417, 2677, 1840, 1132, }, { 11640, 3486, 2113, 1452, 894, }, { 8264, 2473, 1499, 1030, 634, }, { 6344, 1897, 1150, 790, 486, }, }, { { 15640, 4686, 2840, 1952, 1201, }, { 12328, 3693, 2238, 1538, 947, }, { 8920, 2670, 1618, 1112, 684, }, { 6760, 2022, 1226, 842, 518, }, }, { { 16568, 4965, 3009, 2068, 1273, }, { 13048, 3909, 2369, 1628, 1002, }, { 9368, 2805, 1700, 1168, 719, }, { 7208, 2157, 1307, 898, 553, }, }, { { 17528, 5253, 3183, 2188, 1347, }, { 13800, 4134, 2506, 1722, 1060, }, { 9848, 2949, 1787, 1228, 756, }, { 7688, 2301, 1394, 958, 590, }, }, { { 18448, 5529, 3351, 2303, 1417, }, { 14496, 4343, 2632, 1809, 1113, }, { 10288, 3081, 1867, 1283, 790, }, { 7888, 2361, 1431, 983, 605, }, }, { { 19472, 5836, 3537, 2431, 1496, }, { 15312, 4588, 2780, 1911, 1176, }, { 10832, 3244, 1966, 1351, 832, }, { 8432, 2524, 1530, 1051, 647, }, }, { { 20528, 6153, 3729, 2563, 1577, }, { 15936, 4775, 2894, 1989, 1224, }, { 11408, 3417, 2071, 1423, 876, }, { 8768, 2625, 1591, 1093, 673, }, }, { { 21616, 6479, 3927, 2699, 1661, }, { 16816, 5039, 3054, 2099, 1292, }, { 12016, 3599, 2181, 1499, 923, }, { 9136, 2735, 1658, 1139, 701, }, }, { { 22496, 6743, 4087, 2809, 1729, }, { 17728, 5313, 3220, 2213, 1362, }, { 12656, 3791, 2298, 1579, 972, }, { 9776, 2927, 1774, 1219, 750, }, }, { { 23648, 7089, 4296, 2953, 1817, }, { 18672, 5596, 3391, 2331, 1435, }, { 13328, 3993, 2420, 1663, 1024, }, { 10208, 3057, 1852, 1273, 784, }, }, }
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Get a static, NUL terminated, description of an error code, codes that aren't a
+// `BbqrErrorCode` are described as an unknown error
+const char *bbqr_error_message(int32_t code);
+
+// Fill `options` with the default split options
+//
+// # Safety
+//
+// `options` must be null or point to a writable `BbqrSplitOptions`
+BbqrErrorCode bbqr_split_options_default(struct BbqrSplitOptions *options);
+
+// Split `data` into parts, on success `*out` is set to a handle that must be released with
+// [`bbqr_split_free`]
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes, `options` must be null (use the defaults) or point
+// to a valid `BbqrSplitOptions`, and `out` must point to a writable `BbqrSplit *`
+BbqrErrorCode bbqr_split_from_data(const uint8_t *data,
+                                   size_t len,
+                                   uint8_t file_type,
+                                   const struct BbqrSplitOptions *options,
+                                   struct BbqrSplit **out);
+
+// The number of parts in the split, 0 if `split` is null
+//
+// # Safety
+//
+// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+size_t bbqr_split_num_parts(const struct BbqrSplit *split);
+
+// The QR version number (1 to 40) all parts fit in, 0 if `split` is null
+//
+// # Safety
+//
+// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+uint8_t bbqr_split_version(const struct BbqrSplit *split);
+
+// The encoding header byte actually used, 0 if `split` is null
+//
+// Can differ from the requested encoding, Zlib falls back to Base32 when compression doesn't help
+//
+// # Safety
+//
+// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+uint8_t bbqr_split_encoding(const struct BbqrSplit *split);
+
+// The error correction level byte the QR codes must be made with, 0 if `split` is null
+//
+// # Safety
+//
+// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+uint8_t bbqr_split_error_correction(const struct BbqrSplit *split);
+
+// Borrow the NUL terminated part at `index`, null if `split` is null or `index` is out of bounds
+//
+// The returned string is owned by the split and is valid until [`bbqr_split_free`] is called
+//
+// # Safety
+//
+// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+const char *bbqr_split_part(const struct BbqrSplit *split, size_t index);
+
+// Release a split handle, does nothing if `split` is null
+//
+// # Safety
+//
+// `split` must be null or a handle returned by [`bbqr_split_from_data`] that was not freed yet
+void bbqr_split_free(struct BbqrSplit *split);
+
+// Join `num_parts` NUL terminated parts, on success `*out` owns the joined data
+//
+// # Safety
+//
+// `parts` must point to `num_parts` NUL terminated strings, and `out` must point to a writable
+// `BbqrJoined`
+BbqrErrorCode bbqr_join_parts(const char *const *parts, size_t num_parts, struct BbqrJoined *out);
+
+// Release the data owned by `joined` and reset it, does nothing if `joined` is null
+//
+// # Safety
+//
+// `joined` must be null or point to a `BbqrJoined` filled in by this library
+void bbqr_joined_free(struct BbqrJoined *joined);
+
+// Create a new continuous joiner, release it with [`bbqr_continuous_joiner_free`]
+struct BbqrContinuousJoiner *bbqr_continuous_joiner_new(void);
+
+// Add a NUL terminated part to the continuous joiner, the progress is written to `progress`
+//
+// # Safety
+//
+// `joiner` must be a handle returned by [`bbqr_continuous_joiner_new`], `part` must be a NUL
+// terminated string, and `progress` must be null or point to a writable `BbqrJoinProgress`
+BbqrErrorCode bbqr_continuous_joiner_add_part(struct BbqrContinuousJoiner *joiner,
+                                              const char *part,
+                                              struct BbqrJoinProgress *progress);
+
+// Copy the joined data out of a complete continuous joiner, `*out` owns the copy
+//
+// Returns `BBQR_ERROR_CODE_NOT_COMPLETE` if the joiner has not received all the parts yet
+//
+// # Safety
+//
+// `joiner` must be a handle returned by [`bbqr_continuous_joiner_new`], and `out` must point to
+// a writable `BbqrJoined`
+BbqrErrorCode bbqr_continuous_joiner_joined(const struct BbqrContinuousJoiner *joiner,
+                                            struct BbqrJoined *out);
+
+// Release a continuous joiner, does nothing if `joiner` is null
+//
+// # Safety
+//
+// `joiner` must be null or a handle returned by [`bbqr_continuous_joiner_new`] that was not
+// freed yet
+void bbqr_continuous_joiner_free(struct BbqrContinuousJoiner *joiner);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BBQR_H */
@@ -0,0 +1,179 @@
+//! Animate all the parts of a split, as an animated GIF with the `gif` feature, or as an
+//! animated PNG (APNG) with the `png` feature
+//!
+//! Every frame is rendered at the same QR version, `Split::version`, as the spec recommends.
+
+use crate::{
+    render::{Bitmap, RenderError, RenderOptions},
+    split::Split,
+};
+
+/// Animation options, has a default implementation but you can customize it.
+///
+/// Set how each frame is rendered, the frame delay and the loop count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationOptions {
+    /// How to render each frame, the module size sets the pixel scale
+    pub render: RenderOptions,
+    /// How long each frame is shown in milliseconds, default: 200.
+    /// GIF delays are in hundredths of a second, so they are rounded down to 10ms
+    pub frame_delay_ms: u16,
+    /// How many times the animation plays, `0` loops forever, default: 0
+    pub loop_count: u16,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            render: RenderOptions::default(),
+            frame_delay_ms: 200,
+            loop_count: 0,
+        }
+    }
+}
+
+impl Split {
+    /// Render all the parts as one animated GIF, one frame per part
+    #[cfg(feature = "gif")]
+    pub fn to_gif(&self, options: &AnimationOptions) -> Result<Vec<u8>, RenderError> {
+        use gif::{Encoder, Frame, Repeat};
+
+        let mut bitmaps = self.render_bitmaps(&options.render)?;
+        let first = bitmaps.next().transpose()?;
+        let size = frame_size(first.as_ref())?;
+
+        let palette = [options.render.background, options.render.foreground].concat();
+
+        let mut gif = Vec::new();
+        {
+            let mut encoder = Encoder::new(&mut gif, size, size, &palette)?;
+
+            let repeat = match options.loop_count {
+                0 => Repeat::Infinite,
+                // the count is the number of repeats after the first play
+                plays => Repeat::Finite(plays - 1),
+            };
+            encoder.set_repeat(repeat)?;
+
+            // each frame is rendered as it is encoded, so only one bitmap is held at a time
+            for bitmap in first.into_iter().map(Ok).chain(bitmaps) {
+                let bitmap = bitmap?;
+                let frame = Frame {
+                    width: size,
+                    height: size,
+                    delay: options.frame_delay_ms / 10,
+                    buffer: bitmap.pixels.as_slice().into(),
+                    ..Frame::default()
+                };
+
+                encoder.write_frame(&frame)?;
+            }
+        }
+
+        Ok(gif)
+    }
+
+    /// Render all the parts as one animated PNG (APNG), one frame per part
+    #[cfg(feature = "png")]
+    pub fn to_apng(&self, options: &AnimationOptions) -> Result<Vec<u8>, RenderError> {
+        let mut bitmaps = self.render_bitmaps(&options.render)?;
+        let first = bitmaps.next().transpose()?;
+        let size = frame_size(first.as_ref())?;
+
+        let mut apng = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut apng, size.into(), size.into());
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette([options.render.background, options.render.foreground].concat());
+        encoder.set_animated(self.parts.len() as u32, options.loop_count.into())?;
+        encoder.set_frame_delay(options.frame_delay_ms, 1000)?;
+
+        let mut writer = encoder.write_header()?;
+        for bitmap in first.into_iter().map(Ok).chain(bitmaps) {
+            writer.write_image_data(&bitmap?.pixels)?;
+        }
+        writer.finish()?;
+
+        Ok(apng)
+    }
+}
+
+/// The size of every frame, all the parts are the same QR version so they are the same size
+/// as the first
+fn frame_size(first: Option<&Bitmap>) -> Result<u16, RenderError> {
+    let size = first.map_or(0, |bitmap| bitmap.size);
+    size.try_into()
+        .map_err(|_| RenderError::ImageTooLarge(size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode::Encoding, file_type::FileType, qr::Version, split::SplitOptions};
+    use pretty_assertions::assert_eq;
+
+    fn split() -> Split {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: Encoding::Hex,
+            max_version: Version::V10,
+            ..Default::default()
+        };
+
+        Split::try_from_data(&data, FileType::UnicodeText, options).unwrap()
+    }
+
+    fn options() -> AnimationOptions {
+        AnimationOptions {
+            render: RenderOptions {
+                module_size: 2,
+                ..Default::default()
+            },
+            frame_delay_ms: 500,
+            loop_count: 3,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "gif")]
+    fn test_to_gif() {
+        let split = split();
+        let gif = split.to_gif(&options()).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(std::io::Cursor::new(gif))
+            .unwrap();
+
+        let size = ((split.version as u16 + 1) * 4 + 17 + 2 * 4) * 2;
+        assert_eq!(decoder.width(), size);
+        assert_eq!(decoder.height(), size);
+
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 50);
+            assert_eq!(frame.width, size);
+            frames += 1;
+        }
+
+        assert_eq!(frames, split.parts.len());
+        assert_eq!(decoder.repeat(), gif::Repeat::Finite(2));
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_to_apng() {
+        let split = split();
+        let apng = split.to_apng(&options()).unwrap();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(apng));
+        let reader = decoder.read_info().unwrap();
+
+        let animation = reader.info().animation_control.unwrap();
+        assert_eq!(animation.num_frames as usize, split.parts.len());
+        assert_eq!(animation.num_plays, 3);
+
+        let frame = reader.info().frame_control.unwrap();
+        assert_eq!((frame.delay_num, frame.delay_den), (500, 1000));
+    }
+}
@@ -3,13 +3,21 @@
 //! This is useful in a situation where you are receiving parts of a QR code over time, and
 //! you don't have another way of knowing if you have received all the parts.
 
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+
 use log::{debug, warn};
 
 use crate::{
     consts::HEADER_LENGTH,
-    decode,
-    header::{Header, HeaderParseError},
-    join::{self, JoinError, Joined},
+    decode::{self, DecodeLimits, IncrementalDecoder, PartStatus},
+    file_type::FileType,
+    header::{self, Header, HeaderParseError},
+    join::{self, JoinError, JoinMode, Joined},
+    part::Part,
 };
 
 /// Continuously join the parts of the QR codes into one large piece of data
@@ -17,6 +25,8 @@ use crate::{
 /// If the parts are all joined, it will return the joined data
 pub struct ContinuousJoiner {
     internal_state: InternalState,
+    limits: DecodeLimits,
+    mode: JoinMode,
 }
 
 /// The internal state of the continuous joiner, including the header, data, and parts left to join
@@ -24,16 +34,18 @@ enum InternalState {
     /// The initial state, no parts have been added
     Initial,
 
-    InProgress(InProgress),
+    /// Boxed, the decoder is much larger than the other states
+    InProgress(Box<InProgress>),
 
-    /// The state where all parts have been joined
-    Complete(Joined),
+    /// The state where all parts have been joined, keeps the header for the progress
+    Complete(Header, Joined),
 }
 
 /// The state where parts have been added, but not all parts have been joined
 struct InProgress {
     header: Header,
-    data: Vec<String>,
+    /// Decodes each part as it arrives
+    decoder: IncrementalDecoder,
     parts_left: usize,
 }
 
@@ -54,8 +66,29 @@ pub enum ContinuousJoinResult {
     Complete(Joined),
 }
 
+/// Which parts have been received so far, from [`ContinuousJoiner::progress`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinProgress {
+    /// The header shared by all the parts being joined
+    pub header: Header,
+    /// The total number of parts, the same as `header.num_parts`
+    pub total: usize,
+    /// The indices of the parts received so far, in order
+    pub received: Vec<usize>,
+    /// The indices of the parts still missing, in order, empty once complete
+    pub missing: Vec<usize>,
+}
+
+impl JoinProgress {
+    /// True if the part at `index` has been received
+    pub fn is_received(&self, index: usize) -> bool {
+        self.received.binary_search(&index).is_ok()
+    }
+}
+
 /// Errors that can occur when joining data
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
 pub enum ContinuousJoinError {
     #[error(transparent)]
     HeaderParseError(#[from] HeaderParseError),
@@ -77,25 +110,82 @@ impl ContinuousJoinResult {
     fn from_internal_state(internal_state: &InternalState) -> Self {
         match internal_state {
             InternalState::Initial => ContinuousJoinResult::NotStarted,
-            InternalState::InProgress(InProgress { parts_left, .. }) => {
-                ContinuousJoinResult::InProgress {
-                    parts_left: *parts_left,
-                }
+            InternalState::InProgress(in_progress) => ContinuousJoinResult::InProgress {
+                parts_left: in_progress.parts_left,
+            },
+            InternalState::Complete(_, complete) => {
+                ContinuousJoinResult::Complete(complete.clone())
             }
-            InternalState::Complete(complete) => ContinuousJoinResult::Complete(complete.clone()),
         }
     }
 }
 
 impl ContinuousJoiner {
-    /// Create a new continuous joiner
+    /// Create a new continuous joiner, with the default [`DecodeLimits`]
     pub fn new() -> Self {
+        Self::with_limits(DecodeLimits::default())
+    }
+
+    /// Create a new continuous joiner, that fails with [`DecodeError::SizeLimitExceeded`] if the
+    /// decoded data goes over the `limits`
+    ///
+    /// [`DecodeError::SizeLimitExceeded`]: decode::DecodeError::SizeLimitExceeded
+    pub fn with_limits(limits: DecodeLimits) -> Self {
+        Self::with_options(JoinMode::Strict, limits)
+    }
+
+    /// Create a new continuous joiner, with [`JoinMode::Lenient`] the parts of unknown file types
+    /// are joined too
+    pub fn with_mode(mode: JoinMode) -> Self {
+        Self::with_options(mode, DecodeLimits::default())
+    }
+
+    /// Create a new continuous joiner with both a [`JoinMode`] and [`DecodeLimits`]
+    pub fn with_options(mode: JoinMode, limits: DecodeLimits) -> Self {
         Self {
             internal_state: InternalState::Initial,
+            limits,
+            mode,
         }
     }
 
-    /// Add a part to the continuous joiner
+    /// The result of the parts added so far, without adding a part
+    pub fn result(&self) -> ContinuousJoinResult {
+        ContinuousJoinResult::from_internal_state(&self.internal_state)
+    }
+
+    /// Which parts have been received and which are still missing, `None` until a valid part
+    /// has been added
+    pub fn progress(&self) -> Option<JoinProgress> {
+        let (header, received) = match &self.internal_state {
+            InternalState::Initial => return None,
+            InternalState::InProgress(in_progress) => {
+                let received: Vec<usize> = (0..in_progress.header.num_parts)
+                    .filter(|index| in_progress.decoder.is_received(*index))
+                    .collect();
+
+                (&in_progress.header, received)
+            }
+            InternalState::Complete(header, _) => (header, (0..header.num_parts).collect()),
+        };
+
+        let missing = (0..header.num_parts)
+            .filter(|index| received.binary_search(index).is_err())
+            .collect();
+
+        Some(JoinProgress {
+            header: *header,
+            total: header.num_parts,
+            received,
+            missing,
+        })
+    }
+
+    /// Add a part to the continuous joiner.
+    ///
+    /// Each part is decoded as soon as it is added, so the work is spread out over the scan
+    /// instead of all happening when the last part arrives. Zlib data that fails to inflate can't
+    /// be resumed, every part added after that returns the same error
     pub fn add_part(&mut self, part: String) -> Result<ContinuousJoinResult, ContinuousJoinError> {
         if part.is_empty() {
             warn!("tried to add an empty part");
@@ -104,116 +194,112 @@ impl ContinuousJoiner {
             return Ok(result);
         }
 
+        if let InternalState::Complete(_, complete) = &self.internal_state {
+            warn!("tried to add a part after joining is complete");
+            return Ok(ContinuousJoinResult::Complete(complete.clone()));
+        }
+
+        let header = Header::try_from_str_with_mode(&part, self.mode)?;
+        // the index is checked against the header once the part is added
+        let index = header::part_index(&part)?;
+
+        // the index was parsed, so the header is ASCII and this is a char boundary
+        let data = &part[HEADER_LENGTH..];
+
+        self.add_parsed_part(Part {
+            header,
+            index,
+            data,
+        })
+    }
+
+    /// Add a part that was already parsed with [`Part::parse`], see [`ContinuousJoiner::add_part`].
+    /// Parts of unknown file types, parsed with [`JoinMode::Lenient`], fail with
+    /// [`HeaderParseError::InvalidFileType`] unless the joiner is lenient too
+    pub fn add_parsed_part(
+        &mut self,
+        part: Part<'_>,
+    ) -> Result<ContinuousJoinResult, ContinuousJoinError> {
+        if let (JoinMode::Strict, FileType::Other(code)) = (self.mode, part.header.file_type) {
+            return Err(HeaderParseError::InvalidFileType(code as char).into());
+        }
+
         match &mut self.internal_state {
             InternalState::Initial => {
-                let header = Header::try_from_str(&part)?;
-                let mut parts = vec![String::new(); header.num_parts];
-
-                let index = join::get_index_from_part(&part, &header)?;
-
-                let part_data = &part[HEADER_LENGTH..];
-                parts[index] = part_data.to_string();
-
-                let parts_left = header.num_parts - 1;
-
-                // If all parts have been joined, return the joined data
-                // This would happen if there is only one part, in which case state goes
-                // directly from initial -> complete
-                let join_state = if parts_left == 0 {
-                    let data = decode::decode_ordered_parts(&parts, header.encoding)?;
-                    let joined = Joined {
-                        encoding: header.encoding,
-                        file_type: header.file_type,
-                        data,
-                    };
-
-                    let internal_state = InternalState::Complete(joined.clone());
-                    self.internal_state = internal_state;
-
-                    ContinuousJoinResult::Complete(joined)
-                } else {
-                    // else return the in progress state
-                    let internal_state = InternalState::InProgress(InProgress {
-                        header,
-                        data: parts,
-                        parts_left,
-                    });
-
-                    let join_state = ContinuousJoinResult::InProgress { parts_left };
-                    self.internal_state = internal_state;
-
-                    join_state
-                };
+                // only start once the first part is valid
+                let mut in_progress = InProgress::new(part.header, self.limits);
+                in_progress.add_part(part)?;
 
-                Ok(join_state)
+                self.internal_state = InternalState::InProgress(Box::new(in_progress));
             }
 
             InternalState::InProgress(in_progress) => {
-                let part_header = Header::try_from_str(&part)?;
-                let header = &in_progress.header;
-
-                if &part_header != header {
+                if part.header != in_progress.header {
                     return Err(HeaderParseError::InvalidHeaderParts(
                         "Header parts do not match".to_string(),
                     )
                     .into());
                 }
 
-                let index = join::get_index_from_part(&part, &part_header)?;
-                let current_data = &in_progress.data[index];
-
-                // The data for this part is empty.
-                // Which means this is the first time we are seeing data for this part.
-                // Therefore we can decrement the number of parts left to join.
-                if current_data.is_empty() {
-                    debug!("new part added");
-
-                    in_progress.parts_left -= 1;
-                }
+                in_progress.add_part(part)?;
+            }
 
-                let part_data = &part[HEADER_LENGTH..];
-                if !current_data.is_empty() && current_data != part_data {
-                    return Err(JoinError::DuplicatePartWrongContent(index).into());
-                }
+            InternalState::Complete(_, complete) => {
+                warn!("tried to add a part after joining is complete");
 
-                // save the part data, if its not already saved
-                if current_data.is_empty() {
-                    // store the part data in the correct order
-                    in_progress.data[index] = part_data.to_string();
-                }
+                let state = ContinuousJoinResult::Complete(complete.clone());
+                return Ok(state);
+            }
+        }
 
-                // If all parts have been joined, return the joined data
-                let join_state = if in_progress.parts_left == 0 {
-                    let data =
-                        decode::decode_ordered_parts(&in_progress.data, part_header.encoding)?;
-
-                    let joined = Joined {
-                        encoding: part_header.encoding,
-                        file_type: part_header.file_type,
-                        data,
-                    };
-
-                    let internal_state = InternalState::Complete(joined.clone());
-                    self.internal_state = internal_state;
-
-                    ContinuousJoinResult::Complete(joined)
-                } else {
-                    // else return the in progress state
-                    ContinuousJoinResult::InProgress {
-                        parts_left: in_progress.parts_left,
-                    }
+        // If all parts have been joined, return the joined data
+        // This would happen right away if there is only one part, in which case state goes
+        // directly from initial -> complete
+        if let InternalState::InProgress(in_progress) = &mut self.internal_state {
+            if in_progress.parts_left == 0 {
+                let header = in_progress.header;
+                let data = in_progress.decoder.finish()?;
+
+                let joined = Joined {
+                    encoding: header.encoding,
+                    file_type: header.file_type,
+                    data,
                 };
 
-                Ok(join_state)
+                self.internal_state = InternalState::Complete(header, joined);
             }
+        }
 
-            InternalState::Complete(complete) => {
-                warn!("tried to add a part after joining is complete");
+        Ok(ContinuousJoinResult::from_internal_state(
+            &self.internal_state,
+        ))
+    }
+}
 
-                let state = ContinuousJoinResult::Complete(complete.clone());
-                Ok(state)
+impl InProgress {
+    fn new(header: Header, limits: DecodeLimits) -> Self {
+        Self {
+            header,
+            decoder: IncrementalDecoder::new(header.encoding, header.num_parts, limits),
+            parts_left: header.num_parts,
+        }
+    }
+
+    fn add_part(&mut self, part: Part<'_>) -> Result<(), ContinuousJoinError> {
+        let index = join::check_index(part.index, &self.header)?;
+
+        match self.decoder.add_part(index, part.data)? {
+            PartStatus::New => {
+                debug!("new part added");
+                self.parts_left -= 1;
+            }
+            PartStatus::Duplicate => {}
+            PartStatus::Conflicting => {
+                return Err(JoinError::DuplicatePartWrongContent(index).into());
             }
         }
+
+        Ok(())
     }
 }
 
@@ -337,4 +423,254 @@ B$ZU0803CCSQ43RBC6FJ4TBCPCSZDLNPFFXJTEKU45Z34FO2MBVA4U32BF7VVFCH4X73N3CS53NZAUBO
             unreachable!();
         }
     }
+
+    #[test]
+    fn test_decode_limits() {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: crate::encode::Encoding::Base32,
+            ..Default::default()
+        };
+        let split = Split::try_from_data(&data, FileType::UnicodeText, options).unwrap();
+
+        let limits = DecodeLimits {
+            max_decoded_size: Some(1999),
+            max_ratio: None,
+        };
+        let mut joiner = ContinuousJoiner::with_limits(limits);
+
+        let result = split
+            .parts
+            .iter()
+            .try_for_each(|part| joiner.add_part(part.clone()).map(|_| ()));
+
+        assert_eq!(
+            result,
+            Err(decode::DecodeError::SizeLimitExceeded(1999).into())
+        );
+    }
+
+    #[test]
+    fn test_add_part_after_decode_limit() {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: crate::encode::Encoding::Hex,
+            min_split_number: 3,
+            ..Default::default()
+        };
+        let split = Split::try_from_data(&data, FileType::UnicodeText, options).unwrap();
+        assert_eq!(split.parts.len(), 3);
+
+        let limits = DecodeLimits {
+            max_decoded_size: Some(1999),
+            max_ratio: None,
+        };
+        let mut joiner = ContinuousJoiner::with_limits(limits);
+        let error = Err(decode::DecodeError::SizeLimitExceeded(1999).into());
+
+        joiner.add_part(split.parts[0].clone()).unwrap();
+        joiner.add_part(split.parts[1].clone()).unwrap();
+        assert_eq!(joiner.add_part(split.parts[2].clone()), error);
+
+        // the parts are kept, so adding one again gives the same error
+        assert_eq!(joiner.add_part(split.parts[0].clone()), error);
+        assert_eq!(joiner.add_part(split.parts[2].clone()), error);
+    }
+
+    #[test]
+    fn test_decode_limit_before_allocating_zlib_blocks() {
+        let limits = DecodeLimits {
+            max_decoded_size: Some(1000),
+            max_ratio: None,
+        };
+        let mut joiner = ContinuousJoiner::with_limits(limits);
+
+        // the most parts, each 50 compressed bytes
+        let part = format!("B$ZPZZ00{}", "A".repeat(80));
+        assert_eq!(
+            joiner.add_part(part),
+            Err(decode::DecodeError::SizeLimitExceeded(1000).into())
+        );
+        assert_eq!(joiner.result(), ContinuousJoinResult::NotStarted);
+    }
+
+    #[test]
+    // needs std to compress the Zlib parts
+    #[cfg(feature = "std")]
+    fn test_corrupt_zlib_part() {
+        let data: Vec<u8> = (0..3000u32).map(|i| (i * 7 + i / 13) as u8).collect();
+        let options = SplitOptions {
+            min_split_number: 3,
+            ..Default::default()
+        };
+        let split = Split::try_from_data(&data, FileType::UnicodeText, options).unwrap();
+        assert_eq!(split.encoding, crate::encode::Encoding::Zlib);
+        let total = split.parts.len();
+
+        // all ones bytes, an invalid deflate block type
+        let part = &split.parts[0];
+        let corrupt = format!(
+            "{}{}",
+            &part[..HEADER_LENGTH],
+            "7".repeat(part.len() - HEADER_LENGTH)
+        );
+
+        let mut joiner = ContinuousJoiner::new();
+        joiner.add_part(split.parts[1].clone()).unwrap();
+
+        for _ in 0..2 {
+            let error = joiner.add_part(corrupt.clone()).unwrap_err();
+            assert!(matches!(
+                error,
+                ContinuousJoinError::DecodeError(decode::DecodeError::UnableToInflateZlib(_))
+            ));
+        }
+
+        // the stream can't be resumed, so the valid part fails too
+        let error = joiner.add_part(split.parts[0].clone()).unwrap_err();
+        assert!(matches!(error, ContinuousJoinError::DecodeError(_)));
+
+        let progress = joiner.progress().unwrap();
+        assert_eq!(progress.received, vec![1]);
+        assert!(progress.missing.contains(&0));
+        assert_eq!(
+            joiner.result(),
+            ContinuousJoinResult::InProgress {
+                parts_left: total - 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_progress() {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: crate::encode::Encoding::Hex,
+            min_split_number: 4,
+            ..Default::default()
+        };
+        let split = Split::try_from_data(&data, FileType::UnicodeText, options).unwrap();
+        let total = split.parts.len();
+
+        let mut joiner = ContinuousJoiner::new();
+        assert_eq!(joiner.progress(), None);
+        assert_eq!(joiner.result(), ContinuousJoinResult::NotStarted);
+
+        joiner.add_part(split.parts[2].clone()).unwrap();
+        joiner.add_part(split.parts[0].clone()).unwrap();
+        joiner.add_part(split.parts[2].clone()).unwrap();
+
+        let progress = joiner.progress().unwrap();
+        assert_eq!(
+            progress.header,
+            Header::try_from_str(&split.parts[0]).unwrap()
+        );
+        assert_eq!(progress.total, total);
+        assert_eq!(progress.received, vec![0, 2]);
+        assert_eq!(
+            progress.missing,
+            [1].into_iter().chain(3..total).collect::<Vec<_>>()
+        );
+        assert!(progress.is_received(2));
+        assert!(!progress.is_received(1));
+        assert_eq!(
+            joiner.result(),
+            ContinuousJoinResult::InProgress {
+                parts_left: total - 2
+            }
+        );
+
+        for part in &split.parts {
+            joiner.add_part(part.clone()).unwrap();
+        }
+
+        let progress = joiner.progress().unwrap();
+        assert_eq!(progress.received, (0..total).collect::<Vec<_>>());
+        assert!(progress.missing.is_empty());
+        assert!(matches!(joiner.result(), ContinuousJoinResult::Complete(_)));
+    }
+
+    #[test]
+    fn test_malformed_parts() {
+        let mut joiner = ContinuousJoiner::new();
+
+        assert_eq!(
+            joiner.add_part("B$HU02!!FF".to_string()),
+            Err(HeaderParseError::InvalidPartIndex("!!".into()).into())
+        );
+        assert_eq!(
+            joiner.add_part("B$HU02\u{e9}".to_string()),
+            Err(HeaderParseError::NotAscii.into())
+        );
+        assert_eq!(
+            joiner.add_part("B$HU0\u{e9}FF".to_string()),
+            Err(HeaderParseError::NotAscii.into())
+        );
+        assert_eq!(
+            joiner.add_part("B$HU0".to_string()),
+            Err(HeaderParseError::InvalidHeaderSize(5).into())
+        );
+        assert_eq!(joiner.result(), ContinuousJoinResult::NotStarted);
+    }
+
+    #[test]
+    fn test_add_parsed_part() {
+        let mut joiner = ContinuousJoiner::new();
+
+        let part = Part::parse("B$HU0201FF").unwrap();
+        assert_eq!(
+            joiner.add_parsed_part(part),
+            Ok(ContinuousJoinResult::InProgress { parts_left: 1 })
+        );
+
+        let out_of_range = Part { index: 2, ..part };
+        assert_eq!(
+            joiner.add_parsed_part(out_of_range),
+            Err(JoinError::TooManyParts(2, 3).into())
+        );
+
+        let part = Part::parse("B$HU020000").unwrap();
+        let result = joiner.add_parsed_part(part).unwrap();
+
+        let ContinuousJoinResult::Complete(joined) = result else {
+            panic!("expected the join to be complete");
+        };
+        assert_eq!(joined.data, [0x00, 0xFF]);
+    }
+
+    #[test]
+    fn test_unknown_file_type() {
+        let mut joiner = ContinuousJoiner::new();
+        assert_eq!(
+            joiner.add_part("B$HQ0100FF".to_string()),
+            Err(HeaderParseError::InvalidFileType('Q').into())
+        );
+
+        let mut joiner = ContinuousJoiner::with_mode(JoinMode::Lenient);
+        let result = joiner.add_part("B$HQ0100FF".to_string()).unwrap();
+
+        let ContinuousJoinResult::Complete(joined) = result else {
+            panic!("expected the join to be complete");
+        };
+        assert_eq!(joined.file_type, FileType::Other(b'Q'));
+        assert_eq!(joined.data, [0xFF]);
+
+        // a part parsed leniently is still strict in a strict joiner
+        let part = Part::parse_with_mode("B$HQ0100FF", JoinMode::Lenient).unwrap();
+        assert_eq!(
+            ContinuousJoiner::new().add_parsed_part(part),
+            Err(HeaderParseError::InvalidFileType('Q').into())
+        );
+
+        let limits = DecodeLimits {
+            max_decoded_size: Some(0),
+            ..DecodeLimits::unlimited()
+        };
+
+        let mut joiner = ContinuousJoiner::with_options(JoinMode::Lenient, limits);
+        assert_eq!(
+            joiner.add_parsed_part(part),
+            Err(decode::DecodeError::SizeLimitExceeded(0).into())
+        );
+    }
 }
@@ -1,73 +1,806 @@
 //! Join and decode parts of a string using the given encoding.
-use std::io::Read as _;
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 use data_encoding::BASE32_NOPAD;
 use data_encoding::HEXUPPER;
+use miniz_oxide::{
+    inflate::stream::{inflate, InflateState},
+    DataFormat, MZError, MZFlush, MZStatus,
+};
 
 use crate::encode::Encoding;
 
 /// Errors that can occur when decoding data
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 pub enum DecodeError {
-    #[error("Unable to decode hex part: {0}, error: {1}")]
+    /// The index of the part, and the error with the position of the character in the part's
+    /// data, after the header
+    #[error(
+        "Unable to decode hex part: {0}, at character {position}: {kind}",
+        position = .1.position,
+        kind = .1.kind
+    )]
     UnableToDecodeHex(usize, data_encoding::DecodeError),
 
-    #[error("Unable to decode base32 part: {0}, error: {1}")]
+    /// The index of the part, and the error with the position of the character in the part's
+    /// data, after the header
+    #[error(
+        "Unable to decode base32 part: {0}, at character {position}: {kind}",
+        position = .1.position,
+        kind = .1.kind
+    )]
     UnableToDecodeBase32(usize, data_encoding::DecodeError),
 
     #[error("Unable decompress zlib data: {0}")]
     UnableToInflateZlib(String),
+
+    #[error("Part {0} decodes to {1} bytes, but every part before the last is {2} bytes")]
+    InvalidPartLength(usize, usize, usize),
+
+    #[error("The decoded data is larger than the limit of {0} bytes")]
+    SizeLimitExceeded(usize),
+}
+
+/// Limits on the size of the decoded data, so a malicious set of Zlib parts can't inflate to
+/// more memory than the device has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// The most bytes the data can decode to, `None` for no limit
+    pub max_decoded_size: Option<usize>,
+
+    /// The most Zlib data can inflate to, as a multiple of its compressed size, `None` for no
+    /// limit
+    pub max_ratio: Option<usize>,
+}
+
+/// Whether a part added to the [`IncrementalDecoder`] was new
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PartStatus {
+    /// The first time this part was added
+    New,
+    /// The part was already added, with the same content
+    Duplicate,
+    /// The part was already added, with different content
+    Conflicting,
+}
+
+/// Decodes parts as they arrive, instead of all at once after the last part.
+///
+/// Every part except the last decodes to the same number of bytes, so the decoded parts go
+/// straight into one buffer, sized from the first of them to arrive. Zlib data is inflated as
+/// soon as all the parts before it have arrived.
+pub(crate) struct IncrementalDecoder {
+    encoding: Encoding,
+    received: Vec<bool>,
+    /// The decoded size of every part except the last, once one of them has arrived
+    block_size: Option<usize>,
+    /// The decoded parts except the last, in order
+    blocks: Vec<u8>,
+    /// The decoded last part
+    last: Option<Vec<u8>>,
+    limits: DecodeLimits,
+    /// Only used for Zlib
+    inflater: Option<Inflater>,
+    /// The number of parts, from the start, that have been inflated
+    parts_inflated: usize,
+    /// The Zlib stream failed to inflate, it can't be resumed so every later part fails too
+    failed: Option<DecodeError>,
+}
+
+/// Inflates zlib data as it arrives, a part at a time
+pub(crate) struct Inflater {
+    state: Box<InflateState>,
+    output: Vec<u8>,
+    /// The most bytes the stream can inflate to
+    limit: Option<usize>,
+    /// The number of bytes inflated, including any cleared from the output
+    total_out: usize,
+    /// The end of the zlib stream was reached
+    finished: bool,
+}
+
+impl Default for DecodeLimits {
+    /// At most 16 MiB of decoded data, and no limit on the ratio
+    fn default() -> Self {
+        Self {
+            max_decoded_size: Some(16 * 1024 * 1024),
+            max_ratio: None,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// No limits on the decoded data
+    pub fn unlimited() -> Self {
+        Self {
+            max_decoded_size: None,
+            max_ratio: None,
+        }
+    }
+
+    /// The most bytes `compressed_len` bytes of Zlib data can inflate to
+    pub(crate) fn inflate_limit(&self, compressed_len: usize) -> Option<usize> {
+        let ratio_limit = self
+            .max_ratio
+            .map(|ratio| ratio.saturating_mul(compressed_len));
+
+        match (self.max_decoded_size, ratio_limit) {
+            (Some(size_limit), Some(ratio_limit)) => Some(size_limit.min(ratio_limit)),
+            (size_limit, ratio_limit) => size_limit.or(ratio_limit),
+        }
+    }
+
+    /// Error if `size` bytes of decoded data is over the limit
+    pub(crate) fn check_size(&self, size: usize) -> Result<(), DecodeError> {
+        match self.max_decoded_size {
+            Some(limit) if size > limit => Err(DecodeError::SizeLimitExceeded(limit)),
+            _ => Ok(()),
+        }
+    }
 }
 
 pub(crate) fn decode_ordered_parts(
-    parts: &[String],
+    parts: &[impl AsRef<str>],
     encoding: Encoding,
+    limits: DecodeLimits,
 ) -> Result<Vec<u8>, DecodeError> {
     let decoded: Vec<u8> = match encoding {
-        Encoding::Hex => parts
-            .iter()
-            .enumerate()
-            .flat_map(|(index, part)| {
-                HEXUPPER
-                    .decode(part.as_bytes())
-                    .map_err(|error| DecodeError::UnableToDecodeHex(index, error))
-            })
-            .flatten()
-            .collect(),
-
-        Encoding::Base32 => decode_and_join_base32_parts(parts)?,
+        Encoding::Hex | Encoding::Base32 => decode_and_join_parts(parts, encoding)?,
 
         Encoding::Zlib => {
-            let bytes = decode_and_join_base32_parts(parts)?;
+            let bytes = decode_and_join_parts(parts, encoding)?;
 
             // inflate zlib encoded data
-            let decompress = flate2::Decompress::new_with_window_bits(false, 10);
-            let mut decoder =
-                flate2::read::ZlibDecoder::new_with_decompress(&bytes[..], decompress);
-
-            let mut decoded = Vec::new();
-            decoder
-                .read_to_end(&mut decoded)
-                .map_err(|e| DecodeError::UnableToInflateZlib(e.to_string()))?;
+            let mut inflater = Inflater::new(limits.inflate_limit(bytes.len()));
+            inflater.inflate(&bytes)?;
+            inflater.check_finished()?;
 
-            decoded
+            inflater.output
         }
     };
 
+    limits.check_size(decoded.len())?;
+
     Ok(decoded)
 }
 
-fn decode_and_join_base32_parts(parts: &[String]) -> Result<Vec<u8>, DecodeError> {
-    let decoded: Vec<u8> = parts
-        .iter()
-        .enumerate()
-        .flat_map(|(index, part)| {
-            BASE32_NOPAD
-                .decode(part.as_bytes())
-                .map_err(|error| DecodeError::UnableToDecodeBase32(index, error))
-        })
-        .flatten()
-        .collect();
+/// Decode each part and join them, failing on the first part that doesn't decode
+fn decode_and_join_parts(
+    parts: &[impl AsRef<str>],
+    encoding: Encoding,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        decoded.extend(decode_part(encoding, index, part.as_ref())?);
+    }
 
     Ok(decoded)
 }
+
+/// Decode the data of one part, the part without its header
+pub(crate) fn decode_part(
+    encoding: Encoding,
+    index: usize,
+    data: &str,
+) -> Result<Vec<u8>, DecodeError> {
+    specification(encoding)
+        .decode(data.as_bytes())
+        .map_err(|error| decode_error(encoding, index, error))
+}
+
+pub(crate) fn specification(encoding: Encoding) -> data_encoding::Encoding {
+    match encoding {
+        Encoding::Hex => HEXUPPER,
+        Encoding::Base32 | Encoding::Zlib => BASE32_NOPAD,
+    }
+}
+
+pub(crate) fn decode_error(
+    encoding: Encoding,
+    index: usize,
+    error: data_encoding::DecodeError,
+) -> DecodeError {
+    match encoding {
+        Encoding::Hex => DecodeError::UnableToDecodeHex(index, error),
+        Encoding::Base32 | Encoding::Zlib => DecodeError::UnableToDecodeBase32(index, error),
+    }
+}
+
+impl IncrementalDecoder {
+    pub(crate) fn new(encoding: Encoding, num_parts: usize, limits: DecodeLimits) -> Self {
+        // the limit is set once the compressed size is known
+        let inflater = (encoding == Encoding::Zlib).then(|| Inflater::new(None));
+
+        Self {
+            encoding,
+            received: vec![false; num_parts],
+            block_size: None,
+            blocks: Vec::new(),
+            last: None,
+            limits,
+            inflater,
+            parts_inflated: 0,
+            failed: None,
+        }
+    }
+
+    pub(crate) fn is_received(&self, index: usize) -> bool {
+        self.received[index]
+    }
+
+    /// Decode the data of the part at `index`, the part without its header
+    pub(crate) fn add_part(&mut self, index: usize, data: &str) -> Result<PartStatus, DecodeError> {
+        if let Some(error) = &self.failed {
+            return Err(error.clone());
+        }
+
+        if self.received[index] {
+            let decoded = self.decode(index, data)?;
+
+            if decoded == self.decoded_part(index) {
+                return Ok(PartStatus::Duplicate);
+            }
+
+            return Ok(PartStatus::Conflicting);
+        }
+
+        if index == self.received.len() - 1 {
+            let decoded = self.decode(index, data)?;
+            self.check_last_part(decoded.len())?;
+            self.last = Some(decoded);
+        } else {
+            self.decode_block(index, data)?;
+        }
+
+        self.received[index] = true;
+
+        // the part that broke the stream was not joined, it stays missing
+        if let Err(error) = self.inflate_received_parts() {
+            self.received[index] = false;
+            self.failed = Some(error.clone());
+            return Err(error);
+        }
+
+        Ok(PartStatus::New)
+    }
+
+    /// The decoded data, once all the parts have been added
+    pub(crate) fn finish(&mut self) -> Result<Vec<u8>, DecodeError> {
+        debug_assert!(self.received.iter().all(|received| *received));
+
+        if let Some(inflater) = &mut self.inflater {
+            inflater.check_finished()?;
+            return Ok(core::mem::take(&mut inflater.output));
+        }
+
+        // check before taking the blocks, so a part added after the error can still be compared
+        let last = self.last.as_deref().unwrap_or_default();
+        self.limits.check_size(self.blocks.len() + last.len())?;
+
+        let mut data = core::mem::take(&mut self.blocks);
+        data.extend_from_slice(last);
+
+        Ok(data)
+    }
+
+    fn decode(&self, index: usize, data: &str) -> Result<Vec<u8>, DecodeError> {
+        decode_part(self.encoding, index, data)
+    }
+
+    /// Decode a part before the last one in place, into its block of the buffer
+    fn decode_block(&mut self, index: usize, data: &str) -> Result<(), DecodeError> {
+        let specification = specification(self.encoding);
+
+        let length = specification
+            .decode_len(data.len())
+            .map_err(|error| decode_error(self.encoding, index, error))?;
+
+        let block_size = match self.block_size {
+            Some(block_size) => block_size,
+            None => {
+                // the first block sizes the buffer for all of them
+                self.check_last_part_against(length)?;
+
+                // Zlib data is checked as it is inflated too, but a Zlib split only compresses
+                // data that gets smaller, so the compressed data is held to the same limit
+                self.limits.check_size(length * (self.received.len() - 1))?;
+
+                self.blocks = vec![0; length * (self.received.len() - 1)];
+                self.block_size = Some(length);
+                length
+            }
+        };
+
+        if length != block_size {
+            return Err(DecodeError::InvalidPartLength(index, length, block_size));
+        }
+
+        let block = &mut self.blocks[index * block_size..(index + 1) * block_size];
+        if let Err(partial) = specification.decode_mut(data.as_bytes(), block) {
+            return Err(decode_error(self.encoding, index, partial.error));
+        }
+
+        Ok(())
+    }
+
+    /// The decoded data of a part that has been received
+    fn decoded_part(&self, index: usize) -> &[u8] {
+        if index == self.received.len() - 1 {
+            return self.last.as_deref().unwrap_or_default();
+        }
+
+        let block_size = self.block_size.unwrap_or_default();
+        &self.blocks[index * block_size..(index + 1) * block_size]
+    }
+
+    /// The last part can be shorter than the others, but not longer
+    fn check_last_part(&self, length: usize) -> Result<(), DecodeError> {
+        match self.block_size {
+            Some(block_size) if length > block_size => Err(DecodeError::InvalidPartLength(
+                self.received.len() - 1,
+                length,
+                block_size,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the last part, if it arrived first, against the size of the first block
+    fn check_last_part_against(&self, block_size: usize) -> Result<(), DecodeError> {
+        match &self.last {
+            Some(last) if last.len() > block_size => Err(DecodeError::InvalidPartLength(
+                self.received.len() - 1,
+                last.len(),
+                block_size,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Inflate the parts that are next in order, if they have arrived
+    fn inflate_received_parts(&mut self) -> Result<(), DecodeError> {
+        let Some(mut inflater) = self.inflater.take() else {
+            return Ok(());
+        };
+
+        // every part is at most the size of the blocks, or there is just the last part
+        let compressed_len = match self.block_size {
+            Some(block_size) => block_size * self.received.len(),
+            None => self.last.as_ref().map(Vec::len).unwrap_or_default(),
+        };
+        inflater.limit = self.limits.inflate_limit(compressed_len);
+
+        let mut result = Ok(());
+
+        while self.parts_inflated < self.received.len() && self.received[self.parts_inflated] {
+            let input = self.decoded_part(self.parts_inflated);
+
+            result = inflater.inflate(input);
+            if result.is_err() {
+                break;
+            }
+
+            self.parts_inflated += 1;
+        }
+
+        self.inflater = Some(inflater);
+        result
+    }
+}
+
+impl Inflater {
+    /// A raw deflate stream, the BBQr spec uses a 10 bit window which fits in the default one.
+    /// Inflating more than `limit` bytes is an error
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self {
+            state: InflateState::new_boxed(DataFormat::Raw),
+            output: Vec::new(),
+            limit,
+            total_out: 0,
+            finished: false,
+        }
+    }
+
+    /// The data inflated so far, since the last [`Inflater::clear_output`]
+    #[cfg(feature = "std")]
+    pub(crate) fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn clear_output(&mut self) {
+        self.output.clear();
+    }
+
+    /// Error unless the end of the zlib stream has been reached
+    pub(crate) fn check_finished(&self) -> Result<(), DecodeError> {
+        if !self.finished {
+            let error = "the zlib stream is incomplete".to_string();
+            return Err(DecodeError::UnableToInflateZlib(error));
+        }
+
+        Ok(())
+    }
+
+    /// Inflate the next piece of the stream, anything after the end of the stream is ignored
+    pub(crate) fn inflate(&mut self, mut input: &[u8]) -> Result<(), DecodeError> {
+        while !self.finished {
+            // make room for the output, the inflater writes into the spare capacity
+            if self.output.len() == self.output.capacity() {
+                self.output.reserve(input.len().max(1024) * 2);
+            }
+
+            let output_len = self.output.len();
+            let capacity = self.output.capacity();
+            self.output.resize(capacity, 0);
+
+            let result = inflate(
+                &mut self.state,
+                input,
+                &mut self.output[output_len..],
+                MZFlush::None,
+            );
+
+            self.output.truncate(output_len + result.bytes_written);
+            input = &input[result.bytes_consumed..];
+
+            self.total_out += result.bytes_written;
+            if let Some(limit) = self.limit.filter(|limit| self.total_out > *limit) {
+                return Err(DecodeError::SizeLimitExceeded(limit));
+            }
+
+            let output_full = self.output.len() == capacity;
+            let stalled = result.bytes_consumed == 0 && result.bytes_written == 0;
+
+            match result.status {
+                Ok(MZStatus::StreamEnd) => self.finished = true,
+
+                // all the input is inflated, wait for the next part
+                Ok(_) | Err(MZError::Buf) if input.is_empty() && !output_full => break,
+
+                Ok(_) | Err(MZError::Buf) if stalled && !output_full => {
+                    let error = "unable to make progress".to_string();
+                    return Err(DecodeError::UnableToInflateZlib(error));
+                }
+
+                // go around again, with more room if the output is full
+                Ok(_) | Err(MZError::Buf) => {}
+
+                Err(error) => {
+                    let error = format!("invalid deflate stream ({error:?})");
+                    return Err(DecodeError::UnableToInflateZlib(error));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consts::HEADER_LENGTH,
+        file_type::FileType,
+        split::{Split, SplitOptions},
+    };
+    use data_encoding::DecodeKind;
+    use pretty_assertions::assert_eq;
+    use rand::seq::SliceRandom as _;
+
+    fn data() -> Vec<u8> {
+        // compresses, but not to nothing
+        (0..5000u32).map(|i| (i % 251 + i / 97) as u8).collect()
+    }
+
+    fn split(encoding: Encoding) -> Split {
+        let options = SplitOptions {
+            encoding,
+            min_split_number: 6,
+            ..Default::default()
+        };
+
+        let split = Split::try_from_data(&data(), FileType::Psbt, options).unwrap();
+        assert_eq!(split.encoding, encoding);
+
+        split
+    }
+
+    fn part_data(split: &Split, index: usize) -> &str {
+        &split.parts[index][HEADER_LENGTH..]
+    }
+
+    #[test]
+    fn test_incremental_decode_any_order() {
+        for encoding in [Encoding::Hex, Encoding::Base32, Encoding::Zlib] {
+            // needs std to compress the Zlib parts
+            if cfg!(not(feature = "std")) && encoding == Encoding::Zlib {
+                continue;
+            }
+
+            let split = split(encoding);
+            let mut indices: Vec<usize> = (0..split.parts.len()).collect();
+            indices.shuffle(&mut rand::thread_rng());
+
+            let mut decoder =
+                IncrementalDecoder::new(encoding, split.parts.len(), DecodeLimits::default());
+            for index in indices {
+                let status = decoder.add_part(index, part_data(&split, index)).unwrap();
+                assert_eq!(status, PartStatus::New);
+            }
+
+            assert_eq!(decoder.finish().unwrap(), data(), "{encoding:?}");
+        }
+    }
+
+    #[test]
+    // needs std to compress the Zlib parts
+    #[cfg(feature = "std")]
+    fn test_zlib_inflates_received_prefix() {
+        let split = split(Encoding::Zlib);
+        let mut decoder =
+            IncrementalDecoder::new(Encoding::Zlib, split.parts.len(), DecodeLimits::default());
+
+        let inflated = |decoder: &IncrementalDecoder| {
+            let inflater = decoder.inflater.as_ref().unwrap();
+            (decoder.parts_inflated, inflater.output.len())
+        };
+
+        decoder.add_part(1, part_data(&split, 1)).unwrap();
+        assert_eq!(inflated(&decoder), (0, 0));
+
+        decoder.add_part(0, part_data(&split, 0)).unwrap();
+        let (parts_inflated, output_len) = inflated(&decoder);
+        assert_eq!(parts_inflated, 2);
+        assert!(output_len > 0);
+
+        // the block buffer was sized from the first part
+        let block_size = decoder.block_size.unwrap();
+        assert_eq!(decoder.blocks.len(), block_size * (split.parts.len() - 1));
+    }
+
+    #[test]
+    fn test_duplicate_and_conflicting_parts() {
+        let split = split(Encoding::Hex);
+        let mut decoder =
+            IncrementalDecoder::new(Encoding::Hex, split.parts.len(), DecodeLimits::default());
+
+        let last = split.parts.len() - 1;
+        for index in [0, last] {
+            let data = part_data(&split, index);
+            assert_eq!(decoder.add_part(index, data).unwrap(), PartStatus::New);
+            assert_eq!(
+                decoder.add_part(index, data).unwrap(),
+                PartStatus::Duplicate
+            );
+
+            let conflicting = part_data(&split, 1);
+            let conflicting = &conflicting[..data.len().min(conflicting.len())];
+            assert_eq!(
+                decoder.add_part(index, conflicting).unwrap(),
+                PartStatus::Conflicting
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_part_length() {
+        let split = split(Encoding::Hex);
+        let block = part_data(&split, 0);
+        let block_size = block.len() / 2;
+
+        let mut decoder =
+            IncrementalDecoder::new(Encoding::Hex, split.parts.len(), DecodeLimits::default());
+        decoder.add_part(0, block).unwrap();
+
+        let short = &part_data(&split, 1)[2..];
+        assert_eq!(
+            decoder.add_part(1, short),
+            Err(DecodeError::InvalidPartLength(
+                1,
+                block_size - 1,
+                block_size
+            ))
+        );
+        assert!(!decoder.is_received(1));
+
+        // the last part can be shorter, but not longer
+        let last = split.parts.len() - 1;
+        let long = format!("{}00", block);
+        assert_eq!(
+            decoder.add_part(last, &long),
+            Err(DecodeError::InvalidPartLength(
+                last,
+                block_size + 1,
+                block_size
+            ))
+        );
+
+        // or longer than the first block to arrive after it
+        let mut decoder =
+            IncrementalDecoder::new(Encoding::Hex, split.parts.len(), DecodeLimits::default());
+        decoder.add_part(last, &long).unwrap();
+        assert_eq!(
+            decoder.add_part(0, block),
+            Err(DecodeError::InvalidPartLength(
+                last,
+                block_size + 1,
+                block_size
+            ))
+        );
+    }
+
+    #[test]
+    fn test_invalid_characters() {
+        let mut decoder = IncrementalDecoder::new(Encoding::Base32, 2, DecodeLimits::default());
+        assert!(matches!(
+            decoder.add_part(0, "AAAAAAA1"),
+            Err(DecodeError::UnableToDecodeBase32(0, _))
+        ));
+        assert!(matches!(
+            decoder.add_part(1, "AAAAAAA1"),
+            Err(DecodeError::UnableToDecodeBase32(1, _))
+        ));
+    }
+
+    #[test]
+    // needs std to compress the Zlib parts
+    #[cfg(feature = "std")]
+    fn test_incomplete_zlib_stream() {
+        let split = split(Encoding::Zlib);
+        let last = split.parts.len() - 1;
+        let mut decoder =
+            IncrementalDecoder::new(Encoding::Zlib, split.parts.len(), DecodeLimits::default());
+
+        for index in 0..last {
+            decoder.add_part(index, part_data(&split, index)).unwrap();
+        }
+
+        // drop the end of the last part, so the stream never ends
+        let data = part_data(&split, last);
+        decoder.add_part(last, &data[..data.len() - 8]).unwrap();
+
+        assert!(matches!(
+            decoder.finish(),
+            Err(DecodeError::UnableToInflateZlib(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_zlib_size_limits() {
+        // a small decompression bomb, all zeros inflates to about a thousand times the size
+        let bomb = vec![0; 1_000_000];
+        let split = Split::try_from_data(&bomb, FileType::Psbt, Default::default()).unwrap();
+        assert_eq!(split.encoding, Encoding::Zlib);
+
+        let parts: Vec<&str> = split
+            .parts
+            .iter()
+            .map(|part| &part[HEADER_LENGTH..])
+            .collect();
+
+        let limits = DecodeLimits {
+            max_decoded_size: Some(100_000),
+            max_ratio: None,
+        };
+        assert_eq!(
+            decode_ordered_parts(&parts, Encoding::Zlib, limits),
+            Err(DecodeError::SizeLimitExceeded(100_000))
+        );
+
+        let limits = DecodeLimits {
+            max_decoded_size: None,
+            max_ratio: Some(100),
+        };
+        assert!(matches!(
+            decode_ordered_parts(&parts, Encoding::Zlib, limits),
+            Err(DecodeError::SizeLimitExceeded(_))
+        ));
+
+        // stops as soon as the parts received in order inflate past the limit
+        let limits = DecodeLimits {
+            max_decoded_size: Some(100_000),
+            max_ratio: None,
+        };
+        let mut decoder = IncrementalDecoder::new(Encoding::Zlib, parts.len(), limits);
+        assert_eq!(
+            decoder.add_part(0, parts[0]),
+            Err(DecodeError::SizeLimitExceeded(100_000))
+        );
+
+        let decoded = decode_ordered_parts(&parts, Encoding::Zlib, DecodeLimits::default());
+        assert_eq!(decoded.unwrap(), bomb);
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let split = split(Encoding::Hex);
+        let parts: Vec<&str> = split
+            .parts
+            .iter()
+            .map(|part| &part[HEADER_LENGTH..])
+            .collect();
+
+        let limits = DecodeLimits {
+            max_decoded_size: Some(4999),
+            max_ratio: Some(1),
+        };
+        assert_eq!(
+            decode_ordered_parts(&parts, Encoding::Hex, limits),
+            Err(DecodeError::SizeLimitExceeded(4999))
+        );
+
+        // the first block sizes the buffer, and is checked before it is allocated
+        let limits = DecodeLimits {
+            max_decoded_size: Some(1000),
+            max_ratio: None,
+        };
+        let mut decoder = IncrementalDecoder::new(Encoding::Hex, parts.len(), limits);
+        assert_eq!(
+            decoder.add_part(0, parts[0]),
+            Err(DecodeError::SizeLimitExceeded(1000))
+        );
+
+        let limits = DecodeLimits::unlimited();
+        assert_eq!(
+            decode_ordered_parts(&parts, Encoding::Hex, limits).unwrap(),
+            data()
+        );
+    }
+
+    #[test]
+    fn test_corrupted_parts() {
+        for encoding in [Encoding::Hex, Encoding::Base32, Encoding::Zlib] {
+            // needs std to compress the Zlib parts
+            if cfg!(not(feature = "std")) && encoding == Encoding::Zlib {
+                continue;
+            }
+
+            let split = split(encoding);
+            let parts: Vec<String> = (0..split.parts.len())
+                .map(|index| part_data(&split, index).to_string())
+                .collect();
+
+            // a character in neither alphabet
+            let mut invalid_symbol = parts.clone();
+            invalid_symbol[2].replace_range(3..4, "!");
+
+            // cut short, to a length neither can decode
+            let mut invalid_length = parts.clone();
+            invalid_length[1].truncate(9);
+
+            for (parts, index, kind) in [
+                (invalid_symbol, 2, DecodeKind::Symbol),
+                (invalid_length, 1, DecodeKind::Length),
+            ] {
+                let error = decode_ordered_parts(&parts, encoding, DecodeLimits::default())
+                    .expect_err("corrupted part decoded");
+
+                let (error_index, error) = match (encoding, error) {
+                    (Encoding::Hex, DecodeError::UnableToDecodeHex(index, error)) => (index, error),
+                    (
+                        Encoding::Base32 | Encoding::Zlib,
+                        DecodeError::UnableToDecodeBase32(index, error),
+                    ) => (index, error),
+                    (_, error) => panic!("unexpected error for {encoding:?}: {error:?}"),
+                };
+
+                assert_eq!(error_index, index, "{encoding:?}");
+                assert_eq!(error.kind, kind, "{encoding:?}");
+
+                if kind == DecodeKind::Symbol {
+                    assert_eq!(error.position, 3, "{encoding:?}");
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,21 @@
 //! Encoding of data for QR codes, currently supports HEX, Base32, or Zlib
-use std::io::Write as _;
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{self, Read};
 
 use data_encoding::{BASE32_NOPAD, HEXUPPER};
+#[cfg(feature = "std")]
 use flate2::{write::ZlibEncoder, Compress, Compression};
 
 use crate::{
     consts::HEADER_LENGTH,
-    qr::{QrsNeeded, Version},
+    qr::{ErrorCorrectionLevel, QrsNeeded, Version},
 };
 
 /// The encoding to use for the data, HEX, Base32, or Zlib, best to default Zlib
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub enum Encoding {
     Hex,
     Base32,
@@ -46,6 +51,34 @@ impl Encoding {
             Self::Zlib => 8,
         }
     }
+
+    /// The number of bytes that encode to `split_mod` characters
+    #[cfg(feature = "std")]
+    pub(crate) fn bytes_per_split_mod(&self) -> usize {
+        match self {
+            Self::Hex => 1,
+            Self::Base32 => 5,
+            Self::Zlib => 5,
+        }
+    }
+
+    /// The length of `bytes` bytes once encoded, for Zlib the bytes are already compressed
+    #[cfg(feature = "std")]
+    pub(crate) fn encoded_len(&self, bytes: usize) -> usize {
+        match self {
+            Self::Hex => HEXUPPER.encode_len(bytes),
+            Self::Base32 | Self::Zlib => BASE32_NOPAD.encode_len(bytes),
+        }
+    }
+
+    /// Encode bytes to text, for Zlib the bytes are already compressed
+    #[cfg(feature = "std")]
+    pub(crate) fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            Self::Hex => HEXUPPER.encode(bytes),
+            Self::Base32 | Self::Zlib => BASE32_NOPAD.encode(bytes),
+        }
+    }
 }
 
 /// Errors that can occur when encoding data
@@ -80,80 +113,103 @@ impl Encoded {
                 data: BASE32_NOPAD.encode(data),
                 encoding: Encoding::Base32,
             },
-            Encoding::Zlib => {
-                let compress = Compress::new_with_window_bits(Compression::best(), false, 10);
-                let mut encoder =
-                    ZlibEncoder::new_with_compress(Vec::with_capacity(data.len()), compress);
-
-                encoder
-                    .write_all(data)
-                    .map_err(|e| EncodeError::CompressionError(e.to_string()))?;
-
-                let compressed = encoder
-                    .finish()
-                    .map_err(|e| EncodeError::CompressionError(e.to_string()))?;
-
+            Encoding::Zlib => match compress_data(data)? {
                 // only use the compressed version if it's smaller
-                if compressed.len() < data.len() {
-                    // otherwise, use the compressed data
-                    Self {
-                        data: BASE32_NOPAD.encode(&compressed),
-                        encoding: Encoding::Zlib,
-                    }
-                } else {
-                    // if compressed data is larger, use the original data
-                    Self {
-                        data: BASE32_NOPAD.encode(data),
-                        encoding: Encoding::Base32,
-                    }
-                }
-            }
+                Some(compressed) if compressed.len() < data.len() => Self {
+                    data: BASE32_NOPAD.encode(&compressed),
+                    encoding: Encoding::Zlib,
+                },
+                // if compressed data is larger, or can't be compressed, use the original data
+                _ => Self {
+                    data: BASE32_NOPAD.encode(data),
+                    encoding: Encoding::Base32,
+                },
+            },
         };
 
         Ok(encoded)
     }
+}
 
-    pub(crate) fn number_of_qrs_needed(&self, version: Version) -> QrsNeeded {
-        let data_size = self.data.len();
-        let encoding = &self.encoding;
+/// Compress the data for Zlib encoding
+#[cfg(feature = "std")]
+fn compress_data(data: &[u8]) -> Result<Option<Vec<u8>>, EncodeError> {
+    let (compressed, _) =
+        compress(&mut &data[..]).map_err(|e| EncodeError::CompressionError(e.to_string()))?;
 
-        let base_capacity = version.data_capacity() - HEADER_LENGTH;
+    Ok(Some(compressed))
+}
 
-        // we need to adjust the capacity to be a multiple of the encoding split mod
-        let adjusted_capacity = base_capacity - (base_capacity % encoding.split_mod());
+/// Without std there is no compressor that can keep to the 10 bit window, so nothing is
+/// compressed and Zlib falls back to Base32
+#[cfg(not(feature = "std"))]
+fn compress_data(_data: &[u8]) -> Result<Option<Vec<u8>>, EncodeError> {
+    Ok(None)
+}
 
-        let estimated_count = usize::div_ceil(data_size, adjusted_capacity);
+/// Compress everything read from `reader`, as a raw deflate stream with a 10 bit window like
+/// the BBQr spec says, returns the compressed data and the number of bytes read
+#[cfg(feature = "std")]
+pub(crate) fn compress(reader: &mut impl Read) -> io::Result<(Vec<u8>, u64)> {
+    let compress = Compress::new_with_window_bits(Compression::best(), false, 10);
+    let mut encoder = ZlibEncoder::new_with_compress(Vec::new(), compress);
 
-        // if we can fit all the data in one qr code
-        if estimated_count == 1 {
-            return QrsNeeded {
-                version,
-                count: 1,
-                data_per_qr: data_size,
-            };
-        }
+    let read = io::copy(reader, &mut encoder)?;
+    let compressed = encoder.finish()?;
 
-        // the total capacity of our estimated count
-        // all but the last QR need to use adjusted capacity to ensure proper split
-        let total_capacity_of_estimated_count =
-            (estimated_count - 1) * adjusted_capacity + base_capacity;
+    Ok((compressed, read))
+}
 
-        let count = if total_capacity_of_estimated_count >= data_size {
-            estimated_count
-        } else {
-            estimated_count + 1
-        };
+/// The number of QR codes of `version` needed for `data_size` characters of encoded data, `None`
+/// if a QR code at this version and error correction level can't hold a whole split mod of data
+pub(crate) fn qrs_needed(
+    encoding: Encoding,
+    data_size: usize,
+    version: Version,
+    error_correction_level: ErrorCorrectionLevel,
+) -> Option<QrsNeeded> {
+    let base_capacity = version
+        .data_capacity_with_ecl(error_correction_level)
+        .checked_sub(HEADER_LENGTH)?;
+
+    // we need to adjust the capacity to be a multiple of the encoding split mod
+    let adjusted_capacity = base_capacity - (base_capacity % encoding.split_mod());
+    if adjusted_capacity == 0 {
+        return None;
+    }
 
-        QrsNeeded {
+    let estimated_count = usize::div_ceil(data_size, adjusted_capacity);
+
+    // if we can fit all the data in one qr code
+    if estimated_count == 1 {
+        return Some(QrsNeeded {
             version,
-            count,
-            data_per_qr: adjusted_capacity,
-        }
+            count: 1,
+            data_per_qr: data_size,
+        });
     }
+
+    // the total capacity of our estimated count
+    // all but the last QR need to use adjusted capacity to ensure proper split
+    let total_capacity_of_estimated_count =
+        (estimated_count - 1) * adjusted_capacity + base_capacity;
+
+    let count = if total_capacity_of_estimated_count >= data_size {
+        estimated_count
+    } else {
+        estimated_count + 1
+    };
+
+    Some(QrsNeeded {
+        version,
+        count,
+        data_per_qr: adjusted_capacity,
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "std")]
     use crate::decode;
 
     use super::*;
@@ -165,13 +221,41 @@ mod tests {
         let version = Version::V05;
 
         let encoded = Encoded::try_new_from_data(&data, Encoding::Hex).unwrap();
-        let qrs_needed = encoded.number_of_qrs_needed(version);
+        let qrs_needed = qrs_needed(
+            encoded.encoding,
+            encoded.data.len(),
+            version,
+            ErrorCorrectionLevel::Low,
+        )
+        .unwrap();
 
         assert_eq!(encoded.data.len(), 5000);
         assert_eq!(qrs_needed.count, 35);
         assert_eq!(qrs_needed.data_per_qr, 146);
     }
 
+    #[test]
+    fn test_number_of_qrs_needed_with_ecl() {
+        // V05 holds 154 characters at Low and 64 at High, less the header
+        let needed = |ecl| qrs_needed(Encoding::Hex, 5000, Version::V05, ecl).unwrap();
+
+        assert_eq!(needed(ErrorCorrectionLevel::Low).count, 35);
+        assert_eq!(needed(ErrorCorrectionLevel::High).count, 90);
+        assert_eq!(needed(ErrorCorrectionLevel::High).data_per_qr, 56);
+
+        // V01 at High holds 10 characters, 2 after the header, less than a Base32 split mod
+        assert_eq!(
+            qrs_needed(
+                Encoding::Base32,
+                16,
+                Version::V01,
+                ErrorCorrectionLevel::High
+            ),
+            None
+        );
+        assert!(qrs_needed(Encoding::Hex, 16, Version::V01, ErrorCorrectionLevel::High).is_some());
+    }
+
     #[test]
     fn test_encode_hex() {
         let data = b"Hello, world!";
@@ -200,6 +284,17 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(feature = "std"))]
+    fn test_zlib_falls_back_to_base32() {
+        let data = vec![b'A'; 1000];
+        let encoded = Encoded::try_new_from_data(&data, Encoding::Zlib).unwrap();
+
+        assert_eq!(encoded.encoding, Encoding::Base32);
+        assert_eq!(encoded.data, BASE32_NOPAD.encode(&data));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
     fn test_encode_compression() {
         let file_names = [
             "test_data/1in1000out.psbt",
@@ -223,15 +318,22 @@ mod tests {
             let encoded = encoded.unwrap();
             assert_eq!(encoded.encoding, Encoding::Zlib);
 
-            let check = decode::decode_ordered_parts(&[encoded.data.clone()], Encoding::Zlib);
+            let check = decode::decode_ordered_parts(
+                std::slice::from_ref(&encoded.data),
+                Encoding::Zlib,
+                Default::default(),
+            );
 
             assert!(check.is_ok());
 
             let check = check.unwrap();
             assert_eq!(check, raw);
 
-            let decode_as_base32 =
-                decode::decode_ordered_parts(&[encoded.data.clone()], Encoding::Base32);
+            let decode_as_base32 = decode::decode_ordered_parts(
+                std::slice::from_ref(&encoded.data),
+                Encoding::Base32,
+                Default::default(),
+            );
             assert!(decode_as_base32.is_ok());
 
             let decode_as_base32 = decode_as_base32.unwrap();
@@ -0,0 +1,702 @@
+//! C ABI for the `cdylib` and `staticlib` builds, enabled with the `ffi` feature
+//!
+//! Every fallible function returns a [`BbqrErrorCode`], `BBQR_ERROR_CODE_OK` (0) on success. Split results
+//! and continuous joiners are opaque handles that must be released with their matching `_free`
+//! function, and joined data is handed back in a [`BbqrJoined`] that must be released with
+//! [`bbqr_joined_free`].
+//!
+//! The C header is generated with `cbindgen` and lives in `include/bbqr.h`.
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::{
+    continuous_join::{ContinuousJoinError, ContinuousJoinResult, ContinuousJoiner},
+    decode::DecodeError,
+    encode::{EncodeError, Encoding},
+    file_type::FileType,
+    header::HeaderParseError,
+    join::{JoinError, Joined},
+    qr::{ErrorCorrectionLevel, Version},
+    split::{Split, SplitError, SplitOptions},
+};
+
+/// Numeric error codes, mirroring `SplitError`, `JoinError` and `ContinuousJoinError`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BbqrErrorCode {
+    Ok = 0,
+
+    // errors from the C API itself
+    NullPointer = 1,
+    InvalidUtf8 = 2,
+    InvalidEncoding = 3,
+    InvalidFileType = 4,
+    InvalidVersion = 5,
+    NotComplete = 6,
+    Panic = 7,
+    InvalidErrorCorrection = 8,
+
+    // SplitError
+    SplitEmpty = 10,
+    SplitCannotFit = 11,
+    SplitMaxSplitSizeTooLarge = 12,
+    SplitMinSplitTooSmall = 13,
+    SplitInvalidSplitRange = 14,
+    SplitInvalidVersionRange = 15,
+    SplitReadError = 16,
+
+    // EncodeError
+    EncodeEmpty = 20,
+    EncodeCompressionError = 21,
+
+    // JoinError
+    JoinEmpty = 30,
+    JoinConflictingHeaders = 31,
+    JoinTooManyParts = 32,
+    JoinDuplicatePartWrongContent = 33,
+    JoinPartWithNoData = 34,
+    JoinMissingPart = 35,
+    JoinWriteError = 36,
+
+    // HeaderParseError
+    HeaderEmpty = 40,
+    HeaderInvalidEncoding = 41,
+    HeaderInvalidFileType = 42,
+    HeaderInvalidFixedHeader = 43,
+    HeaderInvalidHeaderSize = 44,
+    HeaderInvalidHeaderParts = 45,
+    HeaderInvalidPartIndex = 46,
+    HeaderNotAscii = 47,
+
+    // DecodeError
+    DecodeUnableToDecodeHex = 50,
+    DecodeUnableToDecodeBase32 = 51,
+    DecodeUnableToInflateZlib = 52,
+    DecodeInvalidPartLength = 53,
+    DecodeSizeLimitExceeded = 54,
+}
+
+/// Every error code, to look up the integer a C caller passes in, new codes go here too
+const ERROR_CODES: [BbqrErrorCode; 38] = [
+    BbqrErrorCode::Ok,
+    BbqrErrorCode::NullPointer,
+    BbqrErrorCode::InvalidUtf8,
+    BbqrErrorCode::InvalidEncoding,
+    BbqrErrorCode::InvalidFileType,
+    BbqrErrorCode::InvalidVersion,
+    BbqrErrorCode::NotComplete,
+    BbqrErrorCode::Panic,
+    BbqrErrorCode::InvalidErrorCorrection,
+    BbqrErrorCode::SplitEmpty,
+    BbqrErrorCode::SplitCannotFit,
+    BbqrErrorCode::SplitMaxSplitSizeTooLarge,
+    BbqrErrorCode::SplitMinSplitTooSmall,
+    BbqrErrorCode::SplitInvalidSplitRange,
+    BbqrErrorCode::SplitInvalidVersionRange,
+    BbqrErrorCode::SplitReadError,
+    BbqrErrorCode::EncodeEmpty,
+    BbqrErrorCode::EncodeCompressionError,
+    BbqrErrorCode::JoinEmpty,
+    BbqrErrorCode::JoinConflictingHeaders,
+    BbqrErrorCode::JoinTooManyParts,
+    BbqrErrorCode::JoinDuplicatePartWrongContent,
+    BbqrErrorCode::JoinPartWithNoData,
+    BbqrErrorCode::JoinMissingPart,
+    BbqrErrorCode::JoinWriteError,
+    BbqrErrorCode::HeaderEmpty,
+    BbqrErrorCode::HeaderInvalidEncoding,
+    BbqrErrorCode::HeaderInvalidFileType,
+    BbqrErrorCode::HeaderInvalidFixedHeader,
+    BbqrErrorCode::HeaderInvalidHeaderSize,
+    BbqrErrorCode::HeaderInvalidHeaderParts,
+    BbqrErrorCode::HeaderInvalidPartIndex,
+    BbqrErrorCode::HeaderNotAscii,
+    BbqrErrorCode::DecodeUnableToDecodeHex,
+    BbqrErrorCode::DecodeUnableToDecodeBase32,
+    BbqrErrorCode::DecodeUnableToInflateZlib,
+    BbqrErrorCode::DecodeInvalidPartLength,
+    BbqrErrorCode::DecodeSizeLimitExceeded,
+];
+
+/// Options for [`bbqr_split_from_data`], initialise with [`bbqr_split_options_default`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BbqrSplitOptions {
+    /// The encoding header byte, `'H'`, `'2'` or `'Z'`
+    pub encoding: u8,
+    /// The minimum number of parts to split to
+    pub min_split_number: usize,
+    /// The maximum number of parts to split to
+    pub max_split_number: usize,
+    /// The minimum QR version number, 1 to 40
+    pub min_version: u8,
+    /// The maximum QR version number, 1 to 40
+    pub max_version: u8,
+    /// Spread the data evenly over the parts, instead of leaving a small last part
+    pub balanced: bool,
+    /// The QR error correction level, `'L'`, `'M'`, `'Q'` or `'H'`
+    pub error_correction: u8,
+}
+
+/// Joined data, `data` is owned by the caller and must be released with [`bbqr_joined_free`]
+#[repr(C)]
+#[derive(Debug)]
+pub struct BbqrJoined {
+    /// The encoding header byte, `'H'`, `'2'` or `'Z'`
+    pub encoding: u8,
+    /// The file type header byte, ex: `'P'` for PSBT
+    pub file_type: u8,
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+/// The status of a continuous join, mirrors `ContinuousJoinResult`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BbqrJoinStatus {
+    NotStarted = 0,
+    InProgress = 1,
+    Complete = 2,
+}
+
+/// The progress of a continuous join after adding a part
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BbqrJoinProgress {
+    pub status: BbqrJoinStatus,
+    /// The number of parts left to join, only meaningful when the status is `InProgress`
+    pub parts_left: usize,
+}
+
+/// Opaque handle to the result of a split
+pub struct BbqrSplit {
+    version: Version,
+    encoding: Encoding,
+    error_correction: ErrorCorrectionLevel,
+    parts: Vec<CString>,
+}
+
+/// Opaque handle to a continuous joiner
+pub struct BbqrContinuousJoiner {
+    joiner: ContinuousJoiner,
+    joined: Option<Joined>,
+}
+
+/// Get a static, NUL terminated, description of an error code, codes that aren't a
+/// `BbqrErrorCode` are described as an unknown error
+#[no_mangle]
+pub extern "C" fn bbqr_error_message(code: i32) -> *const c_char {
+    let code = ERROR_CODES.into_iter().find(|known| *known as i32 == code);
+
+    let message: &'static [u8] = match code {
+        Some(BbqrErrorCode::Ok) => b"ok\0",
+        Some(BbqrErrorCode::NullPointer) => b"null pointer\0",
+        Some(BbqrErrorCode::InvalidUtf8) => b"invalid utf-8 string\0",
+        Some(BbqrErrorCode::InvalidEncoding) => b"invalid encoding\0",
+        Some(BbqrErrorCode::InvalidFileType) => b"invalid file type\0",
+        Some(BbqrErrorCode::InvalidVersion) => b"invalid QR version, must be between 1 and 40\0",
+        Some(BbqrErrorCode::NotComplete) => b"joining is not complete\0",
+        Some(BbqrErrorCode::Panic) => b"internal panic\0",
+        Some(BbqrErrorCode::InvalidErrorCorrection) => {
+            b"invalid error correction level, must be L, M, Q or H\0"
+        }
+        Some(BbqrErrorCode::SplitEmpty) => b"no data found\0",
+        Some(BbqrErrorCode::SplitCannotFit) => b"cannot make the data fit\0",
+        Some(BbqrErrorCode::SplitMaxSplitSizeTooLarge) => b"max split size is too large\0",
+        Some(BbqrErrorCode::SplitMinSplitTooSmall) => b"min split size is too small\0",
+        Some(BbqrErrorCode::SplitInvalidSplitRange) => b"invalid split min and max range\0",
+        Some(BbqrErrorCode::SplitInvalidVersionRange) => b"invalid version min and max range\0",
+        Some(BbqrErrorCode::SplitReadError) => b"unable to read data\0",
+        Some(BbqrErrorCode::EncodeEmpty) => b"no data to encode\0",
+        Some(BbqrErrorCode::EncodeCompressionError) => b"unable to compress data\0",
+        Some(BbqrErrorCode::JoinEmpty) => b"no data found\0",
+        Some(BbqrErrorCode::JoinConflictingHeaders) => {
+            b"conflicting/variable file type/encodings/sizes\0"
+        }
+        Some(BbqrErrorCode::JoinTooManyParts) => b"too many parts\0",
+        Some(BbqrErrorCode::JoinDuplicatePartWrongContent) => {
+            b"duplicated part has wrong content\0"
+        }
+        Some(BbqrErrorCode::JoinPartWithNoData) => b"part has no data\0",
+        Some(BbqrErrorCode::JoinMissingPart) => b"missing part\0",
+        Some(BbqrErrorCode::JoinWriteError) => b"unable to write joined data\0",
+        Some(BbqrErrorCode::HeaderEmpty) => b"no header data found\0",
+        Some(BbqrErrorCode::HeaderInvalidEncoding) => b"invalid encoding in header\0",
+        Some(BbqrErrorCode::HeaderInvalidFileType) => b"invalid file type in header\0",
+        Some(BbqrErrorCode::HeaderInvalidFixedHeader) => b"invalid fixed header\0",
+        Some(BbqrErrorCode::HeaderInvalidHeaderSize) => b"invalid header size\0",
+        Some(BbqrErrorCode::HeaderInvalidHeaderParts) => b"invalid header parts\0",
+        Some(BbqrErrorCode::HeaderInvalidPartIndex) => b"invalid part index\0",
+        Some(BbqrErrorCode::HeaderNotAscii) => b"header is not ASCII\0",
+        Some(BbqrErrorCode::DecodeUnableToDecodeHex) => b"unable to decode hex part\0",
+        Some(BbqrErrorCode::DecodeUnableToDecodeBase32) => b"unable to decode base32 part\0",
+        Some(BbqrErrorCode::DecodeUnableToInflateZlib) => b"unable to decompress zlib data\0",
+        Some(BbqrErrorCode::DecodeInvalidPartLength) => {
+            b"part has a different length to the others\0"
+        }
+        Some(BbqrErrorCode::DecodeSizeLimitExceeded) => b"decoded data is larger than the limit\0",
+        None => b"unknown error\0",
+    };
+
+    message.as_ptr().cast()
+}
+
+/// Fill `options` with the default split options
+///
+/// # Safety
+///
+/// `options` must be null or point to a writable `BbqrSplitOptions`
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_options_default(
+    options: *mut BbqrSplitOptions,
+) -> BbqrErrorCode {
+    if options.is_null() {
+        return BbqrErrorCode::NullPointer;
+    }
+
+    let default = SplitOptions::default();
+
+    options.write(BbqrSplitOptions {
+        encoding: default.encoding.as_byte(),
+        min_split_number: default.min_split_number,
+        max_split_number: default.max_split_number,
+        min_version: version_to_number(default.min_version),
+        max_version: version_to_number(default.max_version),
+        balanced: default.balanced,
+        error_correction: error_correction_to_byte(default.error_correction),
+    });
+
+    BbqrErrorCode::Ok
+}
+
+/// Split `data` into parts, on success `*out` is set to a handle that must be released with
+/// [`bbqr_split_free`]
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, `options` must be null (use the defaults) or point
+/// to a valid `BbqrSplitOptions`, and `out` must point to a writable `BbqrSplit *`
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_from_data(
+    data: *const u8,
+    len: usize,
+    file_type: u8,
+    options: *const BbqrSplitOptions,
+    out: *mut *mut BbqrSplit,
+) -> BbqrErrorCode {
+    if data.is_null() || out.is_null() {
+        return BbqrErrorCode::NullPointer;
+    }
+
+    ffi_guard(|| {
+        let bytes = slice::from_raw_parts(data, len);
+        let file_type = FileType::from_byte(file_type).ok_or(BbqrErrorCode::InvalidFileType)?;
+
+        let options = match options.as_ref() {
+            Some(options) => split_options_from_ffi(options)?,
+            None => SplitOptions::default(),
+        };
+
+        let split = Split::try_from_data(bytes, file_type, options)?;
+
+        let parts = split
+            .parts
+            .into_iter()
+            .map(|part| CString::new(part).expect("parts only contain base32/hex characters"))
+            .collect();
+
+        let handle = BbqrSplit {
+            version: split.version,
+            encoding: split.encoding,
+            error_correction: split.error_correction,
+            parts,
+        };
+
+        out.write(Box::into_raw(Box::new(handle)));
+        Ok(())
+    })
+}
+
+/// The number of parts in the split, 0 if `split` is null
+///
+/// # Safety
+///
+/// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_num_parts(split: *const BbqrSplit) -> usize {
+    split.as_ref().map_or(0, |split| split.parts.len())
+}
+
+/// The QR version number (1 to 40) all parts fit in, 0 if `split` is null
+///
+/// # Safety
+///
+/// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_version(split: *const BbqrSplit) -> u8 {
+    split
+        .as_ref()
+        .map_or(0, |split| version_to_number(split.version))
+}
+
+/// The encoding header byte actually used, 0 if `split` is null
+///
+/// Can differ from the requested encoding, Zlib falls back to Base32 when compression doesn't help
+///
+/// # Safety
+///
+/// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_encoding(split: *const BbqrSplit) -> u8 {
+    split.as_ref().map_or(0, |split| split.encoding.as_byte())
+}
+
+/// The error correction level byte the QR codes must be made with, 0 if `split` is null
+///
+/// # Safety
+///
+/// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_error_correction(split: *const BbqrSplit) -> u8 {
+    split
+        .as_ref()
+        .map_or(0, |split| error_correction_to_byte(split.error_correction))
+}
+
+/// Borrow the NUL terminated part at `index`, null if `split` is null or `index` is out of bounds
+///
+/// The returned string is owned by the split and is valid until [`bbqr_split_free`] is called
+///
+/// # Safety
+///
+/// `split` must be null or a handle returned by [`bbqr_split_from_data`]
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_part(split: *const BbqrSplit, index: usize) -> *const c_char {
+    split
+        .as_ref()
+        .and_then(|split| split.parts.get(index))
+        .map_or(ptr::null(), |part| part.as_ptr())
+}
+
+/// Release a split handle, does nothing if `split` is null
+///
+/// # Safety
+///
+/// `split` must be null or a handle returned by [`bbqr_split_from_data`] that was not freed yet
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_split_free(split: *mut BbqrSplit) {
+    if !split.is_null() {
+        drop(Box::from_raw(split));
+    }
+}
+
+/// Join `num_parts` NUL terminated parts, on success `*out` owns the joined data
+///
+/// # Safety
+///
+/// `parts` must point to `num_parts` NUL terminated strings, and `out` must point to a writable
+/// `BbqrJoined`
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_join_parts(
+    parts: *const *const c_char,
+    num_parts: usize,
+    out: *mut BbqrJoined,
+) -> BbqrErrorCode {
+    if parts.is_null() || out.is_null() {
+        return BbqrErrorCode::NullPointer;
+    }
+
+    ffi_guard(|| {
+        let parts = slice::from_raw_parts(parts, num_parts)
+            .iter()
+            .map(|&part| string_from_ptr(part))
+            .collect::<Result<Vec<String>, BbqrErrorCode>>()?;
+
+        let joined = Joined::try_from_parts(parts)?;
+
+        out.write(joined_to_ffi(joined));
+        Ok(())
+    })
+}
+
+/// Release the data owned by `joined` and reset it, does nothing if `joined` is null
+///
+/// # Safety
+///
+/// `joined` must be null or point to a `BbqrJoined` filled in by this library
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_joined_free(joined: *mut BbqrJoined) {
+    let Some(joined) = joined.as_mut() else {
+        return;
+    };
+
+    if !joined.data.is_null() {
+        let data = ptr::slice_from_raw_parts_mut(joined.data, joined.len);
+        drop(Box::from_raw(data));
+    }
+
+    joined.data = ptr::null_mut();
+    joined.len = 0;
+}
+
+/// Create a new continuous joiner, release it with [`bbqr_continuous_joiner_free`]
+#[no_mangle]
+pub extern "C" fn bbqr_continuous_joiner_new() -> *mut BbqrContinuousJoiner {
+    let handle = BbqrContinuousJoiner {
+        joiner: ContinuousJoiner::new(),
+        joined: None,
+    };
+
+    Box::into_raw(Box::new(handle))
+}
+
+/// Add a NUL terminated part to the continuous joiner, the progress is written to `progress`
+///
+/// # Safety
+///
+/// `joiner` must be a handle returned by [`bbqr_continuous_joiner_new`], `part` must be a NUL
+/// terminated string, and `progress` must be null or point to a writable `BbqrJoinProgress`
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_continuous_joiner_add_part(
+    joiner: *mut BbqrContinuousJoiner,
+    part: *const c_char,
+    progress: *mut BbqrJoinProgress,
+) -> BbqrErrorCode {
+    let Some(joiner) = joiner.as_mut() else {
+        return BbqrErrorCode::NullPointer;
+    };
+
+    if part.is_null() {
+        return BbqrErrorCode::NullPointer;
+    }
+
+    ffi_guard(move || {
+        let part = string_from_ptr(part)?;
+
+        let join_progress = match joiner.joiner.add_part(part)? {
+            ContinuousJoinResult::NotStarted => BbqrJoinProgress {
+                status: BbqrJoinStatus::NotStarted,
+                parts_left: 0,
+            },
+            ContinuousJoinResult::InProgress { parts_left } => BbqrJoinProgress {
+                status: BbqrJoinStatus::InProgress,
+                parts_left,
+            },
+            ContinuousJoinResult::Complete(joined) => {
+                joiner.joined = Some(joined);
+
+                BbqrJoinProgress {
+                    status: BbqrJoinStatus::Complete,
+                    parts_left: 0,
+                }
+            }
+        };
+
+        if !progress.is_null() {
+            progress.write(join_progress);
+        }
+
+        Ok(())
+    })
+}
+
+/// Copy the joined data out of a complete continuous joiner, `*out` owns the copy
+///
+/// Returns `BBQR_ERROR_CODE_NOT_COMPLETE` if the joiner has not received all the parts yet
+///
+/// # Safety
+///
+/// `joiner` must be a handle returned by [`bbqr_continuous_joiner_new`], and `out` must point to
+/// a writable `BbqrJoined`
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_continuous_joiner_joined(
+    joiner: *const BbqrContinuousJoiner,
+    out: *mut BbqrJoined,
+) -> BbqrErrorCode {
+    let Some(joiner) = joiner.as_ref() else {
+        return BbqrErrorCode::NullPointer;
+    };
+
+    if out.is_null() {
+        return BbqrErrorCode::NullPointer;
+    }
+
+    match &joiner.joined {
+        Some(joined) => {
+            out.write(joined_to_ffi(joined.clone()));
+            BbqrErrorCode::Ok
+        }
+        None => BbqrErrorCode::NotComplete,
+    }
+}
+
+/// Release a continuous joiner, does nothing if `joiner` is null
+///
+/// # Safety
+///
+/// `joiner` must be null or a handle returned by [`bbqr_continuous_joiner_new`] that was not
+/// freed yet
+#[no_mangle]
+pub unsafe extern "C" fn bbqr_continuous_joiner_free(joiner: *mut BbqrContinuousJoiner) {
+    if !joiner.is_null() {
+        drop(Box::from_raw(joiner));
+    }
+}
+
+/// Run `f`, converting the result to an error code, panics must not unwind into C
+fn ffi_guard(f: impl FnOnce() -> Result<(), BbqrErrorCode>) -> BbqrErrorCode {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => BbqrErrorCode::Ok,
+        Ok(Err(code)) => code,
+        Err(_) => BbqrErrorCode::Panic,
+    }
+}
+
+unsafe fn string_from_ptr(string: *const c_char) -> Result<String, BbqrErrorCode> {
+    if string.is_null() {
+        return Err(BbqrErrorCode::NullPointer);
+    }
+
+    CStr::from_ptr(string)
+        .to_str()
+        .map(ToString::to_string)
+        .map_err(|_| BbqrErrorCode::InvalidUtf8)
+}
+
+fn split_options_from_ffi(options: &BbqrSplitOptions) -> Result<SplitOptions, BbqrErrorCode> {
+    let encoding = Encoding::from_byte(options.encoding).ok_or(BbqrErrorCode::InvalidEncoding)?;
+
+    Ok(SplitOptions {
+        encoding,
+        min_split_number: options.min_split_number,
+        max_split_number: options.max_split_number,
+        min_version: version_from_number(options.min_version)?,
+        max_version: version_from_number(options.max_version)?,
+        balanced: options.balanced,
+        error_correction: error_correction_from_byte(options.error_correction)?,
+    })
+}
+
+fn version_from_number(version_number: u8) -> Result<Version, BbqrErrorCode> {
+    if !(1..=40).contains(&version_number) {
+        return Err(BbqrErrorCode::InvalidVersion);
+    }
+
+    Ok(Version::from_number(version_number as usize))
+}
+
+fn version_to_number(version: Version) -> u8 {
+    version as u8 + 1
+}
+
+fn error_correction_from_byte(byte: u8) -> Result<ErrorCorrectionLevel, BbqrErrorCode> {
+    match byte {
+        b'L' => Ok(ErrorCorrectionLevel::Low),
+        b'M' => Ok(ErrorCorrectionLevel::Medium),
+        b'Q' => Ok(ErrorCorrectionLevel::Quartile),
+        b'H' => Ok(ErrorCorrectionLevel::High),
+        _ => Err(BbqrErrorCode::InvalidErrorCorrection),
+    }
+}
+
+fn error_correction_to_byte(error_correction: ErrorCorrectionLevel) -> u8 {
+    match error_correction {
+        ErrorCorrectionLevel::Low => b'L',
+        ErrorCorrectionLevel::Medium => b'M',
+        ErrorCorrectionLevel::Quartile => b'Q',
+        ErrorCorrectionLevel::High => b'H',
+    }
+}
+
+fn joined_to_ffi(joined: Joined) -> BbqrJoined {
+    let data = joined.data.into_boxed_slice();
+    let len = data.len();
+
+    BbqrJoined {
+        encoding: joined.encoding.as_byte(),
+        file_type: joined.file_type.as_byte(),
+        data: Box::into_raw(data) as *mut u8,
+        len,
+    }
+}
+
+impl From<SplitError> for BbqrErrorCode {
+    fn from(error: SplitError) -> Self {
+        match error {
+            SplitError::Empty => Self::SplitEmpty,
+            SplitError::CannotFit => Self::SplitCannotFit,
+            SplitError::MaxSplitSizeTooLarge(_) => Self::SplitMaxSplitSizeTooLarge,
+            SplitError::MinSplitTooSmall => Self::SplitMinSplitTooSmall,
+            SplitError::InvalidSplitRange => Self::SplitInvalidSplitRange,
+            SplitError::InvalidVersionRange => Self::SplitInvalidVersionRange,
+            SplitError::ReadError(_) => Self::SplitReadError,
+            SplitError::EncodeError(error) => error.into(),
+        }
+    }
+}
+
+impl From<EncodeError> for BbqrErrorCode {
+    fn from(error: EncodeError) -> Self {
+        match error {
+            EncodeError::Empty => Self::EncodeEmpty,
+            EncodeError::CompressionError(_) => Self::EncodeCompressionError,
+        }
+    }
+}
+
+impl From<JoinError> for BbqrErrorCode {
+    fn from(error: JoinError) -> Self {
+        match error {
+            JoinError::Empty => Self::JoinEmpty,
+            JoinError::ConflictingHeaders => Self::JoinConflictingHeaders,
+            JoinError::TooManyParts(_, _) => Self::JoinTooManyParts,
+            JoinError::DuplicatePartWrongContent(_) => Self::JoinDuplicatePartWrongContent,
+            JoinError::PartWithNoData(_) => Self::JoinPartWithNoData,
+            JoinError::MissingPart(_) => Self::JoinMissingPart,
+            JoinError::WriteError(_) => Self::JoinWriteError,
+            JoinError::HeaderParseError(error) => error.into(),
+            JoinError::DecodeError(error) => error.into(),
+        }
+    }
+}
+
+impl From<HeaderParseError> for BbqrErrorCode {
+    fn from(error: HeaderParseError) -> Self {
+        match error {
+            HeaderParseError::Empty => Self::HeaderEmpty,
+            HeaderParseError::InvalidEncoding(_) => Self::HeaderInvalidEncoding,
+            HeaderParseError::InvalidFileType(_) => Self::HeaderInvalidFileType,
+            HeaderParseError::InvalidFixedHeader => Self::HeaderInvalidFixedHeader,
+            HeaderParseError::InvalidHeaderSize(_) => Self::HeaderInvalidHeaderSize,
+            HeaderParseError::InvalidHeaderParts(_) => Self::HeaderInvalidHeaderParts,
+            HeaderParseError::InvalidPartIndex(_) => Self::HeaderInvalidPartIndex,
+            HeaderParseError::NotAscii => Self::HeaderNotAscii,
+        }
+    }
+}
+
+impl From<DecodeError> for BbqrErrorCode {
+    fn from(error: DecodeError) -> Self {
+        match error {
+            DecodeError::UnableToDecodeHex(_, _) => Self::DecodeUnableToDecodeHex,
+            DecodeError::UnableToDecodeBase32(_, _) => Self::DecodeUnableToDecodeBase32,
+            DecodeError::UnableToInflateZlib(_) => Self::DecodeUnableToInflateZlib,
+            DecodeError::InvalidPartLength(_, _, _) => Self::DecodeInvalidPartLength,
+            DecodeError::SizeLimitExceeded(_) => Self::DecodeSizeLimitExceeded,
+        }
+    }
+}
+
+impl From<ContinuousJoinError> for BbqrErrorCode {
+    fn from(error: ContinuousJoinError) -> Self {
+        match error {
+            ContinuousJoinError::HeaderParseError(error) => error.into(),
+            ContinuousJoinError::JoinError(error) => error.into(),
+            ContinuousJoinError::DecodeError(error) => error.into(),
+        }
+    }
+}
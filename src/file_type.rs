@@ -1,6 +1,6 @@
 //! File type enum for the different file types supported by the BBQr standard
 
-use std::fmt::{self, Display, Formatter};
+use core::fmt::{self, Display, Formatter};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// The file type, currently only supports UnicodeText, Transaction, PSBT, Binary, and CBOR
@@ -10,6 +10,11 @@ pub enum FileType {
     Json,
     Cbor,
     UnicodeText,
+    /// The spec's `X` type, for experimental formats
+    Experimental,
+    /// A file type code this version doesn't know, only made when joining with
+    /// [`JoinMode::Lenient`](crate::join::JoinMode::Lenient)
+    Other(u8),
 }
 
 impl FileType {
@@ -20,10 +25,23 @@ impl FileType {
             b'J' => Some(FileType::Json),
             b'C' => Some(FileType::Cbor),
             b'U' => Some(FileType::UnicodeText),
+            b'X' => Some(FileType::Experimental),
             _ => None,
         }
     }
 
+    /// Like [`FileType::from_byte`], but any other uppercase letter or digit is
+    /// [`FileType::Other`], for file types added to the spec after this version
+    pub fn from_byte_lenient(byte: u8) -> Option<FileType> {
+        match Self::from_byte(byte) {
+            Some(file_type) => Some(file_type),
+            None if byte.is_ascii_uppercase() || byte.is_ascii_digit() => {
+                Some(FileType::Other(byte))
+            }
+            None => None,
+        }
+    }
+
     pub fn as_byte(&self) -> u8 {
         match self {
             FileType::Psbt => b'P',
@@ -31,6 +49,8 @@ impl FileType {
             FileType::Json => b'J',
             FileType::Cbor => b'C',
             FileType::UnicodeText => b'U',
+            FileType::Experimental => b'X',
+            FileType::Other(byte) => *byte,
         }
     }
 
@@ -47,6 +67,8 @@ impl Display for FileType {
             FileType::Json => write!(f, "JSON"),
             FileType::Cbor => write!(f, "CBOR"),
             FileType::UnicodeText => write!(f, "Unicode Text"),
+            FileType::Experimental => write!(f, "Experimental"),
+            FileType::Other(byte) => write!(f, "Unknown ({})", *byte as char),
         }
     }
 }
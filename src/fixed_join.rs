@@ -0,0 +1,699 @@
+//! Join the QR code parts one by one, without allocating, into a buffer from the caller
+//!
+//! This is for devices that can't allocate at all, like a secure element. Like the BBQr spec
+//! describes, every part before the last decodes to the same number of bytes, so the buffer is
+//! sized from the first of them to arrive, and the decoded parts go straight into it. The
+//! received parts are kept in a fixed size bitset.
+
+use log::{debug, warn};
+use miniz_oxide::inflate::{
+    core::{
+        decompress, inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF, DecompressorOxide,
+    },
+    TINFLStatus,
+};
+
+use crate::{
+    consts::HEADER_LENGTH,
+    decode,
+    encode::Encoding,
+    file_type::FileType,
+    header::{self, Header, HeaderParseError},
+    join::JoinMode,
+};
+
+/// Enough words for a bit for each of the most parts a header can announce, `MAX_PARTS`
+const RECEIVED_WORDS: usize = (36 * 36 - 1_usize).div_ceil(u64::BITS as usize);
+
+/// Joins the parts into a buffer the caller provides, without allocating.
+///
+/// For Hex and Base32 the buffer needs room for the joined data. For Zlib it needs room for the
+/// compressed data and the inflated data, the parts are inflated into the start of the buffer
+/// once they have all arrived, with about 10KB of stack for the decompressor
+pub struct FixedJoiner<'a> {
+    buffer: &'a mut [u8],
+    /// The header of the first valid part, shared by all the parts
+    header: Option<Header>,
+    received: ReceivedParts,
+    parts_left: usize,
+    /// The decoded size of every part except the last, once one of them has arrived
+    block_size: Option<usize>,
+    /// The decoded size of the last part, once it has arrived
+    last_len: Option<usize>,
+    /// The length of the joined data at the start of the buffer, once complete
+    joined_len: Option<usize>,
+}
+
+/// The result of adding a part to the [`FixedJoiner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixedJoinResult<'a> {
+    /// No valid parts have been added yet
+    NotStarted,
+
+    /// The state where parts have been added, but not all parts have been joined
+    InProgress {
+        /// The number of parts left to join
+        parts_left: usize,
+    },
+
+    /// The state where all parts have been joined
+    Complete(FixedJoined<'a>),
+}
+
+/// The joined data, borrowed from the buffer of the [`FixedJoiner`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedJoined<'a> {
+    pub encoding: Encoding,
+    pub file_type: FileType,
+    pub data: &'a [u8],
+}
+
+/// Errors that can occur when joining into a fixed buffer, like the [`JoinError`] and
+/// [`DecodeError`] of the other joiners, but without the details that need allocating
+///
+/// [`JoinError`]: crate::join::JoinError
+/// [`DecodeError`]: crate::decode::DecodeError
+#[derive(Debug, Clone, Copy, thiserror::Error, PartialEq, Eq)]
+pub enum FixedJoinError {
+    #[error("The data needs at least {0} bytes, but the buffer is {1} bytes")]
+    BufferTooSmall(usize, usize),
+
+    /// The reason the header or the part index isn't valid
+    #[error("Invalid header: {0}")]
+    InvalidHeader(&'static str),
+
+    #[error("Conflicting/variable file type/encodings/sizes")]
+    ConflictingHeaders,
+
+    #[error("Too many parts, expected {0}, got {1}")]
+    TooManyParts(usize, usize),
+
+    #[error("Duplicated part index {0} has wrong content")]
+    DuplicatePartWrongContent(usize),
+
+    #[error("Part with index {0} has no data")]
+    PartWithNoData(usize),
+
+    #[error(
+        "Unable to decode hex part: {0}, at character {position}: {kind}",
+        position = .1.position,
+        kind = .1.kind
+    )]
+    UnableToDecodeHex(usize, data_encoding::DecodeError),
+
+    #[error(
+        "Unable to decode base32 part: {0}, at character {position}: {kind}",
+        position = .1.position,
+        kind = .1.kind
+    )]
+    UnableToDecodeBase32(usize, data_encoding::DecodeError),
+
+    /// The reason the Zlib data can't be inflated
+    #[error("Unable decompress zlib data: {0}")]
+    UnableToInflateZlib(&'static str),
+
+    #[error("Part {0} decodes to {1} bytes, but every part before the last is {2} bytes")]
+    InvalidPartLength(usize, usize, usize),
+}
+
+/// A bit for each part, set once the part has been received
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ReceivedParts([u64; RECEIVED_WORDS]);
+
+impl<'a> FixedJoiner<'a> {
+    /// Create a new joiner, that joins into `buffer`
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            header: None,
+            received: ReceivedParts::default(),
+            parts_left: 0,
+            block_size: None,
+            last_len: None,
+            joined_len: None,
+        }
+    }
+
+    /// The header shared by all the parts, once a valid part has been added
+    pub fn header(&self) -> Option<Header> {
+        self.header
+    }
+
+    /// True if the part at `index` has been received
+    pub fn is_received(&self, index: usize) -> bool {
+        index < RECEIVED_WORDS * 64 && self.received.contains(index)
+    }
+
+    /// The result of the parts added so far, without adding a part
+    pub fn result(&self) -> FixedJoinResult<'_> {
+        match (self.header, self.joined_len) {
+            (None, _) => FixedJoinResult::NotStarted,
+            (Some(_), None) => FixedJoinResult::InProgress {
+                parts_left: self.parts_left,
+            },
+            (Some(header), Some(joined_len)) => FixedJoinResult::Complete(FixedJoined {
+                encoding: header.encoding,
+                file_type: header.file_type,
+                data: &self.buffer[..joined_len],
+            }),
+        }
+    }
+
+    /// Add a part, decoding it straight into the buffer.
+    ///
+    /// If the Zlib data can't be inflated once all the parts have arrived, the part that
+    /// completed them isn't kept, the other parts stay received
+    pub fn add_part(&mut self, part: &str) -> Result<FixedJoinResult<'_>, FixedJoinError> {
+        if part.is_empty() {
+            warn!("tried to add an empty part");
+            return Ok(self.result());
+        }
+
+        if self.joined_len.is_some() {
+            warn!("tried to add a part after joining is complete");
+            return Ok(self.result());
+        }
+
+        let header = Header::parse(part, JoinMode::Strict).map_err(invalid_header)?;
+        if self.header.is_some_and(|started| started != header) {
+            return Err(FixedJoinError::ConflictingHeaders);
+        }
+
+        let index = header::parse_part_index(part).map_err(invalid_header)?;
+        if index >= header.num_parts {
+            return Err(FixedJoinError::TooManyParts(header.num_parts, index + 1));
+        }
+
+        let data = &part[HEADER_LENGTH..];
+
+        if data.is_empty() {
+            return Err(FixedJoinError::PartWithNoData(index));
+        }
+
+        if self.received.contains(index) {
+            if !self.is_stored(header, index, data) {
+                return Err(FixedJoinError::DuplicatePartWrongContent(index));
+            }
+
+            return Ok(self.result());
+        }
+
+        let decoded = if index == header.num_parts - 1 {
+            self.decode_last(header, data)
+        } else {
+            self.decode_block(header, index, data)
+        };
+
+        if let Err(error) = decoded {
+            // only start once the first part is valid
+            if self.header.is_none() {
+                self.reset();
+            }
+
+            return Err(error);
+        }
+
+        if self.header.is_none() {
+            self.header = Some(header);
+            self.parts_left = header.num_parts;
+        }
+
+        debug!("new part added");
+        self.received.insert(index);
+        self.parts_left -= 1;
+
+        if self.parts_left == 0 {
+            if let Err(error) = self.finish(header) {
+                // the part can be added again, so the join can still complete
+                self.received.remove(index);
+                self.parts_left += 1;
+                return Err(error);
+            }
+        }
+
+        Ok(self.result())
+    }
+
+    /// Decode a part before the last one into its block of the buffer
+    fn decode_block(
+        &mut self,
+        header: Header,
+        index: usize,
+        data: &str,
+    ) -> Result<(), FixedJoinError> {
+        let length = decode_len(header.encoding, index, data)?;
+
+        let block_size = match self.block_size {
+            Some(block_size) => block_size,
+            None => {
+                self.size_from_block(header, length)?;
+                length
+            }
+        };
+
+        if length != block_size {
+            return Err(FixedJoinError::InvalidPartLength(index, length, block_size));
+        }
+
+        let block = &mut self.buffer[index * block_size..(index + 1) * block_size];
+        decode_into(header.encoding, index, data, block)
+    }
+
+    /// The first block to arrive sizes the buffer for all of them
+    fn size_from_block(&mut self, header: Header, block_size: usize) -> Result<(), FixedJoinError> {
+        let last = header.num_parts - 1;
+        let last_len = self.last_len.unwrap_or_default();
+
+        if last_len > block_size {
+            return Err(FixedJoinError::InvalidPartLength(
+                last, last_len, block_size,
+            ));
+        }
+
+        let needed = block_size * last + last_len;
+        if needed > self.buffer.len() {
+            return Err(FixedJoinError::BufferTooSmall(needed, self.buffer.len()));
+        }
+
+        // move the last part, if it arrived first, from the end of the buffer to after the blocks
+        if let Some(last_len) = self.last_len {
+            let end = self.buffer.len();
+            self.buffer
+                .copy_within(end - last_len..end, block_size * last);
+        }
+
+        self.block_size = Some(block_size);
+        Ok(())
+    }
+
+    /// Decode the last part, after the blocks, or at the end of the buffer until the size of
+    /// the blocks is known
+    fn decode_last(&mut self, header: Header, data: &str) -> Result<(), FixedJoinError> {
+        let index = header.num_parts - 1;
+        let length = decode_len(header.encoding, index, data)?;
+
+        let start = match self.block_size {
+            Some(block_size) if length > block_size => {
+                return Err(FixedJoinError::InvalidPartLength(index, length, block_size));
+            }
+            Some(block_size) => block_size * index,
+            None if index == 0 => 0,
+            None => self.buffer.len().saturating_sub(length),
+        };
+
+        let end = start + length;
+        if end > self.buffer.len() {
+            return Err(FixedJoinError::BufferTooSmall(end, self.buffer.len()));
+        }
+
+        decode_into(header.encoding, index, data, &mut self.buffer[start..end])?;
+        self.last_len = Some(length);
+
+        Ok(())
+    }
+
+    /// True if the part at `index` was received with the same `data`
+    fn is_stored(&self, header: Header, index: usize, data: &str) -> bool {
+        let last = header.num_parts - 1;
+        let block_size = self.block_size.unwrap_or_default();
+
+        let stored = if index < last {
+            &self.buffer[index * block_size..(index + 1) * block_size]
+        } else {
+            let last_len = self.last_len.unwrap_or_default();
+            let start = match self.block_size {
+                Some(block_size) => block_size * last,
+                None if last == 0 => 0,
+                None => self.buffer.len() - last_len,
+            };
+
+            &self.buffer[start..start + last_len]
+        };
+
+        encodes_to(header.encoding, stored, data)
+    }
+
+    /// All the parts have arrived, for Zlib inflate them
+    fn finish(&mut self, header: Header) -> Result<(), FixedJoinError> {
+        let last = header.num_parts - 1;
+        let decoded_len =
+            self.block_size.unwrap_or_default() * last + self.last_len.unwrap_or_default();
+
+        if header.encoding != Encoding::Zlib {
+            self.joined_len = Some(decoded_len);
+            return Ok(());
+        }
+
+        let inflated_len = self.inflate(decoded_len)?;
+        self.joined_len = Some(inflated_len);
+
+        Ok(())
+    }
+
+    /// Move the compressed data to the end of the buffer, and inflate it into the start. If it
+    /// can't be inflated the compressed data is moved back, where the parts were decoded to
+    fn inflate(&mut self, compressed_len: usize) -> Result<usize, FixedJoinError> {
+        let available = self.buffer.len();
+        let inflated = self.inflate_to_start(compressed_len);
+
+        if inflated.is_err() {
+            self.buffer.copy_within(available - compressed_len.., 0);
+        }
+
+        inflated
+    }
+
+    fn inflate_to_start(&mut self, compressed_len: usize) -> Result<usize, FixedJoinError> {
+        let available = self.buffer.len();
+        self.buffer
+            .copy_within(..compressed_len, available - compressed_len);
+
+        let (output, input) = self.buffer.split_at_mut(available - compressed_len);
+
+        let mut decompressor = DecompressorOxide::new();
+        let flags = TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF;
+        let (status, _, written) = decompress(&mut decompressor, input, output, 0, flags);
+
+        match status {
+            TINFLStatus::Done => Ok(written),
+            TINFLStatus::HasMoreOutput => Err(FixedJoinError::BufferTooSmall(
+                compressed_len + written + 1,
+                available,
+            )),
+            TINFLStatus::NeedsMoreInput | TINFLStatus::FailedCannotMakeProgress => Err(
+                FixedJoinError::UnableToInflateZlib("the zlib stream is incomplete"),
+            ),
+            _ => Err(FixedJoinError::UnableToInflateZlib(
+                "invalid deflate stream",
+            )),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.header = None;
+        self.received = ReceivedParts::default();
+        self.parts_left = 0;
+        self.block_size = None;
+        self.last_len = None;
+        self.joined_len = None;
+    }
+}
+
+impl ReceivedParts {
+    fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+}
+
+/// The reason for a header error, the header is parsed without the details that need allocating
+fn invalid_header(error: HeaderParseError) -> FixedJoinError {
+    let reason = match error {
+        HeaderParseError::Empty => "no data found",
+        HeaderParseError::InvalidEncoding(_) => "invalid encoding",
+        HeaderParseError::InvalidFileType(_) => "invalid file type",
+        HeaderParseError::InvalidFixedHeader => "invalid fixed header",
+        HeaderParseError::InvalidHeaderSize(_) => "not long enough",
+        HeaderParseError::InvalidHeaderParts(_) => "invalid number of parts",
+        HeaderParseError::InvalidPartIndex(_) => "invalid part index",
+        HeaderParseError::NotAscii => "not ASCII",
+    };
+
+    FixedJoinError::InvalidHeader(reason)
+}
+
+fn decode_error(
+    encoding: Encoding,
+    index: usize,
+    error: data_encoding::DecodeError,
+) -> FixedJoinError {
+    match encoding {
+        Encoding::Hex => FixedJoinError::UnableToDecodeHex(index, error),
+        Encoding::Base32 | Encoding::Zlib => FixedJoinError::UnableToDecodeBase32(index, error),
+    }
+}
+
+fn decode_len(encoding: Encoding, index: usize, data: &str) -> Result<usize, FixedJoinError> {
+    decode::specification(encoding)
+        .decode_len(data.len())
+        .map_err(|error| decode_error(encoding, index, error))
+}
+
+fn decode_into(
+    encoding: Encoding,
+    index: usize,
+    data: &str,
+    output: &mut [u8],
+) -> Result<(), FixedJoinError> {
+    decode::specification(encoding)
+        .decode_mut(data.as_bytes(), output)
+        .map_err(|partial| decode_error(encoding, index, partial.error))?;
+
+    Ok(())
+}
+
+/// True if `bytes` encode to `text`, encoding a chunk at a time on the stack
+fn encodes_to(encoding: Encoding, bytes: &[u8], text: &str) -> bool {
+    // a multiple of the bytes in a Hex and a Base32 block
+    const CHUNK: usize = 40;
+
+    let specification = decode::specification(encoding);
+    if specification.encode_len(bytes.len()) != text.len() {
+        return false;
+    }
+
+    let mut encoded = [0; CHUNK * 2];
+    let text_chunk = specification.encode_len(CHUNK);
+
+    bytes
+        .chunks(CHUNK)
+        .zip(text.as_bytes().chunks(text_chunk))
+        .all(|(chunk, text)| {
+            let encoded = &mut encoded[..specification.encode_len(chunk.len())];
+            specification.encode_mut(chunk, encoded);
+            encoded == text
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consts::MAX_PARTS,
+        split::{Split, SplitOptions},
+    };
+    use pretty_assertions::assert_eq;
+
+    fn data() -> Vec<u8> {
+        // compresses, but not to nothing
+        (0..5000u32).map(|i| (i % 251 + i / 97) as u8).collect()
+    }
+
+    fn split(encoding: Encoding) -> Vec<String> {
+        let options = SplitOptions {
+            encoding,
+            min_split_number: 6,
+            ..Default::default()
+        };
+
+        let split = Split::try_from_data(&data(), FileType::Psbt, options).unwrap();
+        assert_eq!(split.encoding, encoding);
+
+        split.parts
+    }
+
+    fn join<'a>(
+        joiner: &'a mut FixedJoiner,
+        parts: &[String],
+    ) -> Result<FixedJoinResult<'a>, FixedJoinError> {
+        for part in parts {
+            joiner.add_part(part)?;
+        }
+
+        Ok(joiner.result())
+    }
+
+    #[test]
+    fn test_received_parts_fit() {
+        assert!(RECEIVED_WORDS * 64 >= MAX_PARTS);
+    }
+
+    #[test]
+    fn test_fixed_join() {
+        let encodings = if cfg!(feature = "std") {
+            &[Encoding::Hex, Encoding::Base32, Encoding::Zlib][..]
+        } else {
+            &[Encoding::Hex, Encoding::Base32][..]
+        };
+
+        for encoding in encodings {
+            let mut parts = split(*encoding);
+            // the last part first, then the rest backwards
+            parts.reverse();
+
+            let mut buffer = [0; 10_000];
+            let mut joiner = FixedJoiner::new(&mut buffer);
+
+            assert_eq!(joiner.result(), FixedJoinResult::NotStarted);
+
+            joiner.add_part(&parts[0]).unwrap();
+            assert!(joiner.is_received(parts.len() - 1));
+            assert!(!joiner.is_received(0));
+
+            let expected = FixedJoinResult::InProgress {
+                parts_left: parts.len() - 2,
+            };
+            assert_eq!(joiner.add_part(&parts[1]).unwrap(), expected);
+
+            let FixedJoinResult::Complete(joined) = join(&mut joiner, &parts[2..]).unwrap() else {
+                panic!("not complete for {encoding:?}");
+            };
+
+            assert_eq!(joined.encoding, *encoding);
+            assert_eq!(joined.file_type, FileType::Psbt);
+            assert_eq!(joined.data, data());
+        }
+    }
+
+    #[test]
+    fn test_duplicate_and_conflicting_parts() {
+        let parts = split(Encoding::Base32);
+        let last = parts.len() - 1;
+
+        let mut buffer = [0; 5000];
+        let mut joiner = FixedJoiner::new(&mut buffer);
+
+        for index in [last, 0, last, 0] {
+            joiner.add_part(&parts[index]).unwrap();
+        }
+
+        assert_eq!(
+            joiner.result(),
+            FixedJoinResult::InProgress {
+                parts_left: parts.len() - 2
+            }
+        );
+
+        for index in [0, last] {
+            let mut conflicting = parts[index].clone();
+            conflicting.replace_range(HEADER_LENGTH..HEADER_LENGTH + 8, "AAAAAAAA");
+
+            assert_eq!(
+                joiner.add_part(&conflicting),
+                Err(FixedJoinError::DuplicatePartWrongContent(index))
+            );
+        }
+
+        let other = Split::try_from_data(b"other", FileType::Json, Default::default()).unwrap();
+        assert_eq!(
+            joiner.add_part(&other.parts[0]),
+            Err(FixedJoinError::ConflictingHeaders)
+        );
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        let parts = split(Encoding::Hex);
+
+        // the first block announces the size of all of them
+        let mut buffer = [0; 4000];
+        let mut joiner = FixedJoiner::new(&mut buffer);
+
+        assert!(matches!(
+            joiner.add_part(&parts[0]),
+            Err(FixedJoinError::BufferTooSmall(_, 4000))
+        ));
+        assert_eq!(joiner.result(), FixedJoinResult::NotStarted);
+
+        // room for the blocks, but not the last part
+        let block_size = (parts[0].len() - HEADER_LENGTH) / 2;
+        let mut buffer = vec![0; block_size * (parts.len() - 1)];
+        let mut joiner = FixedJoiner::new(&mut buffer);
+
+        join(&mut joiner, &parts[..parts.len() - 1]).unwrap();
+        assert!(matches!(
+            joiner.add_part(&parts[parts.len() - 1]),
+            Err(FixedJoinError::BufferTooSmall(..))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_buffer_too_small_to_inflate() {
+        let parts = split(Encoding::Zlib);
+
+        // room for the compressed data, but not the inflated data
+        let mut buffer = [0; 5000];
+        let mut joiner = FixedJoiner::new(&mut buffer);
+
+        assert!(matches!(
+            join(&mut joiner, &parts),
+            Err(FixedJoinError::BufferTooSmall(..))
+        ));
+
+        // only the part that completed them isn't kept
+        let last = parts.len() - 1;
+        assert_eq!(
+            joiner.result(),
+            FixedJoinResult::InProgress { parts_left: 1 }
+        );
+        assert!((0..last).all(|index| joiner.is_received(index)));
+        assert!(!joiner.is_received(last));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_corrupt_zlib_part() {
+        let parts = split(Encoding::Zlib);
+
+        let mut corrupt = parts[0].clone();
+        corrupt.replace_range(HEADER_LENGTH..HEADER_LENGTH + 8, "AAAAAAAA");
+
+        let mut buffer = [0; 10_000];
+        let mut joiner = FixedJoiner::new(&mut buffer);
+        join(&mut joiner, &parts[1..]).unwrap();
+
+        assert!(matches!(
+            joiner.add_part(&corrupt),
+            Err(FixedJoinError::UnableToInflateZlib(_))
+        ));
+        assert_eq!(
+            joiner.result(),
+            FixedJoinResult::InProgress { parts_left: 1 }
+        );
+        assert!(!joiner.is_received(0));
+
+        // the parts kept were moved back, so the join completes with the part scanned again
+        let FixedJoinResult::Complete(joined) = joiner.add_part(&parts[0]).unwrap() else {
+            panic!("not complete");
+        };
+
+        assert_eq!(joined.data, data());
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut buffer = [0; 100];
+        let mut joiner = FixedJoiner::new(&mut buffer);
+
+        assert_eq!(
+            joiner.add_part("B$HP0!00FF"),
+            Err(FixedJoinError::InvalidHeader("invalid number of parts"))
+        );
+        assert_eq!(
+            joiner.add_part("B$HP02!!FF"),
+            Err(FixedJoinError::InvalidHeader("invalid part index"))
+        );
+        assert_eq!(
+            joiner.add_part("B$HP0202FF"),
+            Err(FixedJoinError::TooManyParts(2, 3))
+        );
+    }
+}
@@ -1,13 +1,12 @@
 //! The header module contains the header structure and parsing logic
 
-use std::fmt::{self, Display, Formatter};
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{self, Display, Formatter};
 
-use radix_fmt::radix;
-
-use crate::{consts::HEADER_LENGTH, encode::Encoding, file_type::FileType};
+use crate::{consts::HEADER_LENGTH, encode::Encoding, file_type::FileType, join::JoinMode};
 
 /// Errors that can occur when parsing a header
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
 pub enum HeaderParseError {
     #[error("No data found")]
     Empty,
@@ -26,6 +25,12 @@ pub enum HeaderParseError {
 
     #[error("Invalid header parts {0}")]
     InvalidHeaderParts(String),
+
+    #[error("Invalid part index {0}")]
+    InvalidPartIndex(String),
+
+    #[error("Header is not ASCII")]
+    NotAscii,
 }
 
 /// The header structure, includes the encoding, file type, and number of parts
@@ -53,7 +58,7 @@ impl Display for Header {
 
 impl Header {
     /// Create a new header with the given encoding, file type, and number of parts
-    pub(crate) fn new(encoding: Encoding, file_type: FileType, num_parts: usize) -> Self {
+    pub fn new(encoding: Encoding, file_type: FileType, num_parts: usize) -> Self {
         Self {
             encoding,
             file_type,
@@ -61,20 +66,43 @@ impl Header {
         }
     }
 
-    /// Try to parse a header from a string
-    pub(crate) fn try_from_str(header_str: &str) -> Result<Self, HeaderParseError> {
+    /// Try to parse a header from a string, the start of a part including its index
+    pub fn try_from_str(header_str: &str) -> Result<Self, HeaderParseError> {
+        Self::try_from_str_with_mode(header_str, JoinMode::Strict)
+    }
+
+    /// Try to parse a header, with [`JoinMode::Lenient`] unknown file types are parsed as
+    /// [`FileType::Other`]
+    pub fn try_from_str_with_mode(
+        header_str: &str,
+        mode: JoinMode,
+    ) -> Result<Self, HeaderParseError> {
+        Self::parse(header_str, mode).map_err(|error| match error {
+            HeaderParseError::InvalidHeaderParts(_) => HeaderParseError::InvalidHeaderParts(
+                format!("Invalid number of parts: {}", &header_str[4..6]),
+            ),
+            error => error,
+        })
+    }
+
+    /// Parse a header without allocating, [`HeaderParseError::InvalidHeaderParts`] is returned
+    /// without the invalid number of parts
+    pub(crate) fn parse(header_str: &str, mode: JoinMode) -> Result<Self, HeaderParseError> {
         if header_str.is_empty() {
             return Err(HeaderParseError::Empty);
         }
 
-        // note: okay to work directyl with bytes here, since only ASCII is used in the protocol
-        let first_header_bytes = header_str.as_bytes();
-
         let header_len = header_str.len();
         if header_len < HEADER_LENGTH {
             return Err(HeaderParseError::InvalidHeaderSize(header_len));
         }
 
+        // only ASCII is used in the protocol, once checked it's okay to slice by bytes
+        let first_header_bytes = &header_str.as_bytes()[..HEADER_LENGTH];
+        if !first_header_bytes.is_ascii() {
+            return Err(HeaderParseError::NotAscii);
+        }
+
         let fixed_header = &header_str[0..2];
         if fixed_header != "B$" {
             return Err(HeaderParseError::InvalidFixedHeader);
@@ -85,15 +113,17 @@ impl Header {
             .ok_or(HeaderParseError::InvalidEncoding(encoding_byte as char))?;
 
         let file_type_byte = first_header_bytes[3];
-        let file_type = FileType::from_byte(file_type_byte)
-            .ok_or(HeaderParseError::InvalidFileType(file_type_byte as char))?;
+        let file_type = match mode {
+            JoinMode::Strict => FileType::from_byte(file_type_byte),
+            JoinMode::Lenient => FileType::from_byte_lenient(file_type_byte),
+        };
+
+        let file_type =
+            file_type.ok_or(HeaderParseError::InvalidFileType(file_type_byte as char))?;
 
         let num_parts_str = &header_str[4..6];
-        let num_parts = usize::from_str_radix(num_parts_str, 36).map_err(|_| {
-            HeaderParseError::InvalidHeaderParts(format!(
-                "Invalid number of parts: {num_parts_str}",
-            ))
-        })?;
+        let num_parts = base_36_to_int(num_parts_str)
+            .ok_or(HeaderParseError::InvalidHeaderParts(String::new()))?;
 
         let header = Header {
             encoding,
@@ -105,15 +135,61 @@ impl Header {
     }
 }
 
+/// The index of a part, from the two base36 digits after the header
+pub(crate) fn part_index(part: &str) -> Result<usize, HeaderParseError> {
+    parse_part_index(part).map_err(|error| match error {
+        HeaderParseError::InvalidPartIndex(_) => {
+            let index = String::from_utf8_lossy(&part.as_bytes()[HEADER_LENGTH - 2..HEADER_LENGTH]);
+            HeaderParseError::InvalidPartIndex(index.into_owned())
+        }
+        error => error,
+    })
+}
+
+/// The index of a part without allocating, [`HeaderParseError::InvalidPartIndex`] is returned
+/// without the invalid index
+pub(crate) fn parse_part_index(part: &str) -> Result<usize, HeaderParseError> {
+    let index_bytes = part
+        .as_bytes()
+        .get(HEADER_LENGTH - 2..HEADER_LENGTH)
+        .ok_or(HeaderParseError::InvalidHeaderSize(part.len()))?;
+
+    // work on bytes, the index may not be ASCII or fall on a char boundary
+    let index = index_bytes.iter().try_fold(0, |index, byte| {
+        Some(index * 36 + (*byte as char).to_digit(36)? as usize)
+    });
+
+    index.ok_or(HeaderParseError::InvalidPartIndex(String::new()))
+}
+
+/// Parse base36 digits, without the sign `usize::from_str_radix` allows
+fn base_36_to_int(digits: &str) -> Option<usize> {
+    digits.chars().try_fold(0, |num, digit| {
+        Some(num * 36 + digit.to_digit(36)? as usize)
+    })
+}
+
 pub(crate) fn int_to_padded_base_36(num: usize) -> String {
-    // if less than 36 need padding
-    if num < 36 {
-        // using in format directly does not adding padding for some reason
-        let base_36 = radix(num, 36).to_string().to_uppercase();
-        return format!("{:0>2}", base_36);
+    const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    let mut digits = Vec::new();
+    let mut rest = num;
+
+    loop {
+        digits.push(DIGITS[rest % 36]);
+        rest /= 36;
+
+        if rest == 0 {
+            break;
+        }
+    }
+
+    // pad to at least two digits
+    if digits.len() < 2 {
+        digits.push(b'0');
     }
 
-    format!("{:#}", radix(num, 36))
+    digits.iter().rev().map(|digit| *digit as char).collect()
 }
 
 #[cfg(test)]
@@ -153,4 +229,76 @@ mod tests {
         assert_eq!(int_to_padded_base_36(556), "FG");
         assert_eq!(int_to_padded_base_36(1295), "ZZ");
     }
+
+    #[test]
+    fn test_malformed_headers() {
+        assert_eq!(
+            Header::try_from_str("B$ZU"),
+            Err(HeaderParseError::InvalidHeaderSize(4))
+        );
+        assert_eq!(
+            Header::try_from_str("B$ZUé01A"),
+            Err(HeaderParseError::NotAscii)
+        );
+        assert_eq!(
+            Header::try_from_str("éB$ZU01A"),
+            Err(HeaderParseError::NotAscii)
+        );
+        assert!(matches!(
+            Header::try_from_str("B$ZU+101"),
+            Err(HeaderParseError::InvalidHeaderParts(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_file_types() {
+        assert_eq!(
+            Header::try_from_str("B$ZX0100").map(|header| header.file_type),
+            Ok(FileType::Experimental)
+        );
+        assert_eq!(
+            Header::try_from_str("B$ZQ0100"),
+            Err(HeaderParseError::InvalidFileType('Q'))
+        );
+
+        let header = Header::try_from_str_with_mode("B$ZQ0100", JoinMode::Lenient).unwrap();
+        assert_eq!(header.file_type, FileType::Other(b'Q'));
+        assert_eq!(header.to_string(), "B$ZQ01");
+
+        // known file types never parse as other, and the encoding must still be known
+        assert_eq!(
+            Header::try_from_str_with_mode("B$ZP0100", JoinMode::Lenient).map(|h| h.file_type),
+            Ok(FileType::Psbt)
+        );
+        assert_eq!(
+            Header::try_from_str_with_mode("B$ZQ0100", JoinMode::Lenient),
+            Ok(header)
+        );
+        assert_eq!(
+            Header::try_from_str_with_mode("B$YQ0100", JoinMode::Lenient),
+            Err(HeaderParseError::InvalidEncoding('Y'))
+        );
+        assert_eq!(
+            Header::try_from_str_with_mode("B$Z%0100", JoinMode::Lenient),
+            Err(HeaderParseError::InvalidFileType('%'))
+        );
+    }
+
+    #[test]
+    fn test_part_index() {
+        assert_eq!(part_index("B$ZU0801"), Ok(1));
+        assert_eq!(part_index("B$ZU08zz"), Ok(1295));
+        assert_eq!(
+            part_index("B$ZU08"),
+            Err(HeaderParseError::InvalidHeaderSize(6))
+        );
+        assert_eq!(
+            part_index("B$ZU08!!"),
+            Err(HeaderParseError::InvalidPartIndex("!!".into()))
+        );
+        assert_eq!(
+            part_index("B$ZU080é"),
+            Err(HeaderParseError::InvalidPartIndex("0\u{FFFD}".into()))
+        );
+    }
 }
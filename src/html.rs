@@ -0,0 +1,273 @@
+//! Export a split as one self-contained HTML page that plays the QR codes
+//!
+//! All the parts are embedded as SVG, the page has play/pause, speed control, a frame counter
+//! and manual stepping, and needs nothing but a browser to work offline.
+
+use std::fmt::Write as _;
+
+use fast_qr::convert::{svg::SvgBuilder, Builder as _};
+
+use crate::{
+    render::{RenderError, RenderOptions},
+    split::Split,
+};
+
+/// HTML player options, has a default implementation but you can customize it.
+///
+/// Set how each frame is rendered, the starting frame delay and the page title
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtmlOptions {
+    /// How to render each frame, the module size sets the displayed size in CSS pixels
+    pub render: RenderOptions,
+    /// How long each frame is shown in milliseconds when the page opens, default: 200
+    pub frame_delay_ms: u16,
+    /// The title of the page, default: "BBQr"
+    pub title: String,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            render: RenderOptions::default(),
+            frame_delay_ms: 200,
+            title: "BBQr".to_string(),
+        }
+    }
+}
+
+impl Split {
+    /// Export all the parts as one HTML page that plays them in a loop
+    pub fn to_html(&self, options: &HtmlOptions) -> Result<String, RenderError> {
+        if options.render.module_size == 0 {
+            return Err(RenderError::InvalidModuleSize);
+        }
+
+        let qr_codes = self.render_qr_codes()?;
+
+        let quiet_zone = options.render.quiet_zone as usize;
+        let mut svg_builder = SvgBuilder::default();
+        svg_builder
+            .margin(quiet_zone)
+            .module_color(options.render.foreground)
+            .background_color(options.render.background);
+
+        let modules = qr_codes.first().map_or(0, |qr| qr.size) + 2 * quiet_zone;
+        let size = modules * options.render.module_size as usize;
+
+        let mut frames = String::new();
+        for (index, qr) in qr_codes.iter().enumerate() {
+            let hidden = if index == 0 { "" } else { " hidden" };
+            let svg = svg_builder.to_str(qr);
+
+            // writing to a string can't fail
+            let _ = writeln!(frames, r#"<div class="frame"{hidden}>{svg}</div>"#);
+        }
+
+        let html = fill_template(
+            TEMPLATE,
+            &[
+                ("title", &escape_html(&options.title)),
+                ("size", &size.to_string()),
+                ("delay", &options.frame_delay_ms.max(1).to_string()),
+                ("total", &qr_codes.len().to_string()),
+                ("frames", &frames),
+            ],
+        );
+
+        Ok(html)
+    }
+}
+
+/// Replace the `{name}` placeholders in one pass, so a value with a placeholder in it, like a
+/// title, is left as it is. Other braces, in the CSS and the script, are kept
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut html = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        html.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let value = values.iter().find_map(|(name, value)| {
+            let after = rest.strip_prefix(name)?.strip_prefix('}')?;
+            Some((*value, after))
+        });
+
+        match value {
+            Some((value, after)) => {
+                html.push_str(value);
+                rest = after;
+            }
+            None => html.push('{'),
+        }
+    }
+
+    html.push_str(rest);
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body { font-family: sans-serif; display: flex; flex-direction: column; align-items: center; margin: 1em; }
+.frame svg { width: {size}px; height: {size}px; max-width: 95vw; max-height: 95vh; shape-rendering: crispEdges; }
+.controls { display: flex; gap: 0.5em; align-items: center; margin-top: 1em; }
+#counter { min-width: 5em; text-align: center; font-variant-numeric: tabular-nums; }
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<div id="frames">
+{frames}</div>
+<div class="controls">
+<button id="previous" title="Previous frame (left arrow)">&#9664;</button>
+<button id="play" title="Play or pause (space)">Pause</button>
+<button id="next" title="Next frame (right arrow)">&#9654;</button>
+<span id="counter">1 / {total}</span>
+<label>Delay <input id="delay" type="number" min="10" step="10" value="{delay}"> ms</label>
+</div>
+<script>
+(function () {
+  var frames = document.querySelectorAll(".frame");
+  var counter = document.getElementById("counter");
+  var play = document.getElementById("play");
+  var delay = document.getElementById("delay");
+  var current = 0;
+  var timer = null;
+
+  function show(index) {
+    frames[current].hidden = true;
+    current = (index + frames.length) % frames.length;
+    frames[current].hidden = false;
+    counter.textContent = (current + 1) + " / " + frames.length;
+  }
+
+  function start() {
+    stop();
+    timer = setInterval(function () { show(current + 1); }, Math.max(10, Number(delay.value) || {delay}));
+    play.textContent = "Pause";
+  }
+
+  function stop() {
+    if (timer !== null) { clearInterval(timer); }
+    timer = null;
+    play.textContent = "Play";
+  }
+
+  function step(offset) {
+    stop();
+    show(current + offset);
+  }
+
+  play.addEventListener("click", function () { timer === null ? start() : stop(); });
+  document.getElementById("previous").addEventListener("click", function () { step(-1); });
+  document.getElementById("next").addEventListener("click", function () { step(1); });
+  delay.addEventListener("change", function () { if (timer !== null) { start(); } });
+
+  document.addEventListener("keydown", function (event) {
+    if (event.target === delay) { return; }
+    if (event.key === " ") { event.preventDefault(); play.click(); }
+    if (event.key === "ArrowLeft") { step(-1); }
+    if (event.key === "ArrowRight") { step(1); }
+  });
+
+  if (frames.length > 1) { start(); } else { stop(); }
+})();
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode::Encoding, file_type::FileType, qr::Version, split::SplitOptions};
+    use pretty_assertions::assert_eq;
+
+    fn split() -> Split {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: Encoding::Hex,
+            max_version: Version::V10,
+            ..Default::default()
+        };
+
+        Split::try_from_data(&data, FileType::UnicodeText, options).unwrap()
+    }
+
+    #[test]
+    fn test_to_html() {
+        let split = split();
+        let options = HtmlOptions {
+            render: RenderOptions {
+                module_size: 3,
+                quiet_zone: 2,
+                foreground: [0x11, 0x22, 0x33],
+                background: [0xff, 0xee, 0xdd],
+            },
+            frame_delay_ms: 300,
+            title: "PSBT <1 of 2>".to_string(),
+        };
+
+        let html = split.to_html(&options).unwrap();
+
+        assert_eq!(html.matches("<svg").count(), split.parts.len());
+        assert_eq!(
+            html.matches(r#"<div class="frame" hidden>"#).count(),
+            split.parts.len() - 1
+        );
+        assert!(html.contains(&format!("1 / {}", split.parts.len())));
+        assert!(html.contains(r#"value="300""#));
+        assert!(html.contains("<title>PSBT &lt;1 of 2&gt;</title>"));
+        assert!(html.contains("#112233"));
+        assert!(html.contains("#ffeedd"));
+
+        let modules = (split.version as usize + 1) * 4 + 17 + 2 * 2;
+        assert!(html.contains(&format!("width: {}px", modules * 3)));
+
+        // no placeholders left over
+        assert!(!html.contains("{frames}"));
+        assert!(!html.contains("{delay}"));
+    }
+
+    #[test]
+    fn test_placeholders_in_title() {
+        let split = split();
+        let options = HtmlOptions {
+            title: "{frames} {delay} {total}".to_string(),
+            ..Default::default()
+        };
+
+        let html = split.to_html(&options).unwrap();
+
+        assert!(html.contains("<title>{frames} {delay} {total}</title>"));
+        assert!(html.contains("<h1>{frames} {delay} {total}</h1>"));
+        assert_eq!(html.matches("<svg").count(), split.parts.len());
+        assert!(html.contains(r#"value="200""#));
+    }
+
+    #[test]
+    fn test_invalid_module_size() {
+        let options = HtmlOptions {
+            render: RenderOptions {
+                module_size: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let result = split().to_html(&options);
+        assert!(matches!(result, Err(RenderError::InvalidModuleSize)));
+    }
+}
@@ -1,15 +1,23 @@
 //! Join multiple QR codes into one large piece of data
 
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
 use crate::{
     consts::HEADER_LENGTH,
-    decode,
+    decode::{self, DecodeLimits},
     encode::Encoding,
     file_type::FileType,
-    header::{Header, HeaderParseError},
+    header::{self, Header, HeaderParseError},
+    part::Part,
 };
 
 /// Errors that can occur when joining data
-#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[derive(Debug, Clone, thiserror::Error, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
 pub enum JoinError {
     #[error("No data found")]
     Empty,
@@ -29,6 +37,9 @@ pub enum JoinError {
     #[error("Missing part, with index {0}")]
     MissingPart(usize),
 
+    #[error("Unable to write joined data: {0}")]
+    WriteError(String),
+
     #[error(transparent)]
     HeaderParseError(#[from] HeaderParseError),
 
@@ -36,6 +47,19 @@ pub enum JoinError {
     DecodeError(#[from] decode::DecodeError),
 }
 
+/// How the file type in the headers is checked when joining
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JoinMode {
+    /// Only join the file types in the spec, others fail with
+    /// [`HeaderParseError::InvalidFileType`]
+    #[default]
+    Strict,
+
+    /// Also join the parts of unknown file types, as [`FileType::Other`]. The encoding must still
+    /// be Hex, Base32 or Zlib to decode the data
+    Lenient,
+}
+
 /// Joined data structure, includes the encoding, file type, and raw data in bytes
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Joined {
@@ -52,32 +76,188 @@ pub struct Joined {
 }
 
 impl Joined {
+    /// Join all the parts, with the default [`DecodeLimits`]
     pub fn try_from_parts(parts: Vec<String>) -> Result<Self, JoinError> {
-        let (header, data) = join_qrs(parts)?;
+        Self::try_from_parts_with_limits(parts, DecodeLimits::default())
+    }
+
+    /// Join all the parts, with [`JoinMode::Lenient`] the parts of unknown file types are joined
+    /// too
+    pub fn try_from_parts_with_mode(parts: Vec<String>, mode: JoinMode) -> Result<Self, JoinError> {
+        Self::try_from_parts_with_options(parts, mode, DecodeLimits::default())
+    }
+
+    /// Join all the parts, failing with [`DecodeError::SizeLimitExceeded`] if the decoded data
+    /// goes over the `limits`
+    ///
+    /// [`DecodeError::SizeLimitExceeded`]: decode::DecodeError::SizeLimitExceeded
+    pub fn try_from_parts_with_limits(
+        parts: Vec<String>,
+        limits: DecodeLimits,
+    ) -> Result<Self, JoinError> {
+        Self::try_from_parts_with_options(parts, JoinMode::Strict, limits)
+    }
+
+    /// Join all the parts with both a [`JoinMode`] and [`DecodeLimits`]
+    pub fn try_from_parts_with_options(
+        parts: Vec<String>,
+        mode: JoinMode,
+        limits: DecodeLimits,
+    ) -> Result<Self, JoinError> {
+        let (header, data) = join_qrs(parts, limits, mode)?;
         Ok(Self {
             encoding: header.encoding,
             file_type: header.file_type,
             data,
         })
     }
+
+    /// Join parts that were already parsed with [`Part::parse`], with the default
+    /// [`DecodeLimits`]
+    pub fn try_from_parsed_parts(parts: &[Part<'_>]) -> Result<Self, JoinError> {
+        Self::try_from_parsed_parts_with_limits(parts, DecodeLimits::default())
+    }
+
+    /// Join parts that were already parsed with [`Part::parse`], failing with
+    /// [`DecodeError::SizeLimitExceeded`] if the decoded data goes over the `limits`
+    ///
+    /// [`DecodeError::SizeLimitExceeded`]: decode::DecodeError::SizeLimitExceeded
+    pub fn try_from_parsed_parts_with_limits(
+        parts: &[Part<'_>],
+        limits: DecodeLimits,
+    ) -> Result<Self, JoinError> {
+        let (header, orderered_parts) = order_parsed_parts(parts)?;
+        let data = decode::decode_ordered_parts(&orderered_parts, header.encoding, limits)?;
+
+        Ok(Self {
+            encoding: header.encoding,
+            file_type: header.file_type,
+            data,
+        })
+    }
+
+    /// Join all the parts, writing the decoded data to `writer` a part at a time instead of
+    /// keeping it in memory, for Zlib the data is inflated as it is written.
+    ///
+    /// Returns the header of the parts, with the encoding and file type. On an error, some of
+    /// the data may already have been written. The default [`DecodeLimits`] apply
+    #[cfg(feature = "std")]
+    pub fn try_from_parts_to_writer(
+        parts: Vec<String>,
+        writer: impl Write,
+    ) -> Result<Header, JoinError> {
+        Self::try_from_parts_to_writer_with_limits(parts, writer, DecodeLimits::default())
+    }
+
+    /// Join all the parts to `writer`, like [`Joined::try_from_parts_to_writer`], failing with
+    /// [`DecodeError::SizeLimitExceeded`] if the decoded data goes over the `limits`
+    ///
+    /// [`DecodeError::SizeLimitExceeded`]: decode::DecodeError::SizeLimitExceeded
+    #[cfg(feature = "std")]
+    pub fn try_from_parts_to_writer_with_limits(
+        parts: Vec<String>,
+        mut writer: impl Write,
+        limits: DecodeLimits,
+    ) -> Result<Header, JoinError> {
+        join_qrs_to_writer(&parts, &mut writer, limits)
+    }
 }
 
 // Take scanned data, put into order, decode, return type code and raw data bytes
-fn join_qrs(input_parts: Vec<String>) -> Result<(Header, Vec<u8>), JoinError> {
-    let header = get_and_verify_headers(input_parts.as_slice())?;
+fn join_qrs(
+    input_parts: Vec<String>,
+    limits: DecodeLimits,
+    mode: JoinMode,
+) -> Result<(Header, Vec<u8>), JoinError> {
+    let (header, orderered_parts) = order_parts(&input_parts, mode)?;
+    let data = decode::decode_ordered_parts(&orderered_parts, header.encoding, limits)?;
 
-    // pre-allocate the parts, so we can insert them in the correct order, faster than sorting
-    let mut orderered_parts = vec![String::new(); header.num_parts];
+    Ok((header, data))
+}
 
-    for part in input_parts {
-        if part.is_empty() {
-            continue;
+/// Decode the parts in order, writing each one to `writer` once it is decoded
+#[cfg(feature = "std")]
+fn join_qrs_to_writer(
+    input_parts: &[String],
+    writer: &mut impl Write,
+    limits: DecodeLimits,
+) -> Result<Header, JoinError> {
+    let (header, orderered_parts) = order_parts(input_parts, JoinMode::Strict)?;
+    let write_error = |error: io::Error| JoinError::WriteError(error.to_string());
+
+    let mut written = 0;
+
+    let mut inflater = (header.encoding == Encoding::Zlib).then(|| {
+        // each Base32 character is 5 bits
+        let compressed_len = orderered_parts.iter().map(|part| part.len() * 5 / 8).sum();
+        decode::Inflater::new(limits.inflate_limit(compressed_len))
+    });
+
+    for (index, part) in orderered_parts.iter().enumerate() {
+        let decoded = decode::decode_part(header.encoding, index, part)?;
+
+        match &mut inflater {
+            Some(inflater) => {
+                inflater.inflate(&decoded)?;
+                writer.write_all(inflater.output()).map_err(write_error)?;
+                inflater.clear_output();
+            }
+            None => {
+                written += decoded.len();
+                limits.check_size(written)?;
+                writer.write_all(&decoded).map_err(write_error)?;
+            }
         }
+    }
+
+    if let Some(inflater) = &inflater {
+        inflater.check_finished()?;
+    }
 
-        let index = get_index_from_part(&part, &header)?;
+    writer.flush().map_err(write_error)?;
 
-        let current_part_content = &orderered_parts[index];
-        let part_data = &part[HEADER_LENGTH..];
+    Ok(header)
+}
+
+/// Verify the headers, and put the data of each part in order, without the headers
+fn order_parts(input_parts: &[String], mode: JoinMode) -> Result<(Header, Vec<&str>), JoinError> {
+    let header = get_and_verify_headers(input_parts, mode)?;
+
+    let parts = input_parts
+        .iter()
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let index = get_index_from_part(part, &header)?;
+
+            // the index was parsed, so the header is ASCII and this is a char boundary
+            let data = &part[HEADER_LENGTH..];
+
+            Ok(Part {
+                header,
+                index,
+                data,
+            })
+        })
+        .collect::<Result<Vec<_>, JoinError>>()?;
+
+    order_parsed_parts(&parts)
+}
+
+/// Put the data of each parsed part in order, all the parts must have the same header
+fn order_parsed_parts<'a>(parts: &[Part<'a>]) -> Result<(Header, Vec<&'a str>), JoinError> {
+    let header = parts.first().ok_or(JoinError::Empty)?.header;
+    if parts.iter().any(|part| part.header != header) {
+        return Err(JoinError::ConflictingHeaders);
+    }
+
+    // pre-allocate the parts, so we can insert them in the correct order, faster than sorting
+    let mut orderered_parts = vec![""; header.num_parts];
+
+    for part in parts {
+        let index = check_index(part.index, &header)?;
+
+        let current_part_content = orderered_parts[index];
+        let part_data = part.data;
 
         if !current_part_content.is_empty() && current_part_content != part_data {
             return Err(JoinError::DuplicatePartWrongContent(index));
@@ -88,7 +268,7 @@ fn join_qrs(input_parts: Vec<String>) -> Result<(Header, Vec<u8>), JoinError> {
         }
 
         // store the part data in the correct order
-        orderered_parts[index] = part_data.to_string();
+        orderered_parts[index] = part_data;
     }
 
     // check if any part is missing
@@ -98,13 +278,11 @@ fn join_qrs(input_parts: Vec<String>) -> Result<(Header, Vec<u8>), JoinError> {
         }
     }
 
-    let data = decode::decode_ordered_parts(&orderered_parts, header.encoding)?;
-
-    Ok((header, data))
+    Ok((header, orderered_parts))
 }
 
 /// Verify that all the headers have the same variable filetype, encodings and sizes
-fn get_and_verify_headers(parts: &[String]) -> Result<Header, JoinError> {
+fn get_and_verify_headers(parts: &[String], mode: JoinMode) -> Result<Header, JoinError> {
     if parts.is_empty() {
         return Err(JoinError::Empty);
     }
@@ -112,12 +290,12 @@ fn get_and_verify_headers(parts: &[String]) -> Result<Header, JoinError> {
     // find first non-empty line
     let first_header = parts
         .iter()
-        .find(|line| !line.is_empty())
+        .find(|line| !line.trim().is_empty())
         .ok_or(JoinError::Empty)?;
 
-    let header = Header::try_from_str(first_header)?;
+    let header = Header::try_from_str_with_mode(first_header, mode)?;
 
-    // verify that all the headers are the same
+    // verify that all the headers are the same, compare bytes as the parts may not be ASCII
     for part in parts.iter().skip(1) {
         if part.trim().is_empty() {
             continue;
@@ -127,7 +305,7 @@ fn get_and_verify_headers(parts: &[String]) -> Result<Header, JoinError> {
             return Err(JoinError::ConflictingHeaders);
         }
 
-        if part[0..6] != first_header[0..6] {
+        if part.as_bytes()[0..6] != first_header.as_bytes()[0..6] {
             return Err(JoinError::ConflictingHeaders);
         }
     }
@@ -135,11 +313,13 @@ fn get_and_verify_headers(parts: &[String]) -> Result<Header, JoinError> {
     Ok(header)
 }
 
+/// Get the index of the part, checking that it is in range of the header
 pub(crate) fn get_index_from_part(part: &str, header: &Header) -> Result<usize, JoinError> {
-    // get the index of the the current part
-    // already checked in get_and_verify_headers that the header is long enough
-    let index = usize::from_str_radix(&part[6..8], 36).unwrap();
+    let index = header::part_index(part)?;
+    check_index(index, header)
+}
 
+pub(crate) fn check_index(index: usize, header: &Header) -> Result<usize, JoinError> {
     // more parts than the header says, error
     if index >= header.num_parts {
         return Err(JoinError::TooManyParts(header.num_parts, index + 1));
@@ -161,7 +341,7 @@ mod tests {
             .map(String::from)
             .collect::<Vec<String>>();
 
-        let header = get_and_verify_headers(&parts);
+        let header = get_and_verify_headers(&parts, JoinMode::Strict);
 
         assert!(header.is_ok());
         assert_eq!(
@@ -181,7 +361,7 @@ mod tests {
             .map(String::from)
             .collect::<Vec<String>>();
 
-        let header = get_and_verify_headers(&parts);
+        let header = get_and_verify_headers(&parts, JoinMode::Strict);
 
         assert!(header.is_err());
         assert_eq!(header.unwrap_err(), JoinError::Empty);
@@ -194,9 +374,211 @@ mod tests {
             .map(String::from)
             .collect::<Vec<String>>();
 
-        let header = get_and_verify_headers(&parts);
+        let header = get_and_verify_headers(&parts, JoinMode::Strict);
 
         assert!(header.is_err());
         assert_eq!(header.unwrap_err(), JoinError::ConflictingHeaders);
     }
+
+    #[test]
+    fn test_malformed_parts() {
+        let join = |parts: &[&str]| {
+            let parts = parts.iter().map(|part| part.to_string()).collect();
+            Joined::try_from_parts(parts)
+        };
+
+        assert_eq!(
+            join(&["B$HU0200FF", "B$HU02!!FF"]),
+            Err(HeaderParseError::InvalidPartIndex("!!".into()).into())
+        );
+        assert_eq!(
+            join(&["B$HU0200FF", "B$HU02\u{e9}"]),
+            Err(HeaderParseError::InvalidPartIndex("\u{e9}".into()).into())
+        );
+        assert_eq!(
+            join(&["B$HU0200FF", "B$H\u{e9}0201FF"]),
+            Err(JoinError::ConflictingHeaders)
+        );
+        assert_eq!(
+            join(&["B$HU02\u{e9}FF"]),
+            Err(HeaderParseError::NotAscii.into())
+        );
+        assert_eq!(join(&["   ", "B$HU0100FF"]).unwrap().data, [0xFF]);
+    }
+
+    #[test]
+    fn test_join_unknown_file_type() {
+        let parts = vec!["B$HQ0201FF".to_string(), "B$HQ020000".to_string()];
+
+        assert_eq!(
+            Joined::try_from_parts(parts.clone()),
+            Err(HeaderParseError::InvalidFileType('Q').into())
+        );
+
+        let joined = Joined::try_from_parts_with_mode(parts.clone(), JoinMode::Lenient).unwrap();
+        assert_eq!(joined.file_type, FileType::Other(b'Q'));
+        assert_eq!(joined.data, [0x00, 0xFF]);
+
+        let limits = DecodeLimits {
+            max_decoded_size: Some(1),
+            ..DecodeLimits::unlimited()
+        };
+
+        assert_eq!(
+            Joined::try_from_parts_with_options(parts, JoinMode::Lenient, limits),
+            Err(decode::DecodeError::SizeLimitExceeded(1).into())
+        );
+    }
+
+    #[test]
+    fn test_join_parsed_parts() {
+        let parts = ["B$HU0201FF", "B$HU020000"];
+        let parts = parts
+            .iter()
+            .map(|part| Part::parse(part))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let joined = Joined::try_from_parsed_parts(&parts).unwrap();
+        assert_eq!(joined.data, [0x00, 0xFF]);
+
+        let conflicting = [parts[0], Part::parse("B$HU0300FF").unwrap()];
+        assert_eq!(
+            Joined::try_from_parsed_parts(&conflicting),
+            Err(JoinError::ConflictingHeaders)
+        );
+        assert_eq!(Joined::try_from_parsed_parts(&[]), Err(JoinError::Empty));
+
+        // the fields are public, so the index is checked again
+        let out_of_range = [Part {
+            index: 2,
+            ..parts[0]
+        }];
+        assert_eq!(
+            Joined::try_from_parsed_parts(&out_of_range),
+            Err(JoinError::TooManyParts(2, 3))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    fn split(data: &[u8], encoding: Encoding) -> Vec<String> {
+        let options = crate::split::SplitOptions {
+            encoding,
+            min_split_number: 5,
+            ..Default::default()
+        };
+
+        let split = crate::split::Split::try_from_data(data, FileType::Psbt, options).unwrap();
+        assert_eq!(split.encoding, encoding);
+
+        split.parts
+    }
+
+    #[cfg(feature = "std")]
+    /// Records each write, to check the data is written a piece at a time
+    #[derive(Default)]
+    struct RecordingWriter {
+        writes: Vec<Vec<u8>>,
+    }
+
+    #[cfg(feature = "std")]
+    impl Write for RecordingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_join_to_writer() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251 + i / 97) as u8).collect();
+
+        for encoding in [Encoding::Hex, Encoding::Base32, Encoding::Zlib] {
+            let mut parts = split(&data, encoding);
+            parts.reverse();
+
+            let mut writer = RecordingWriter::default();
+            let header = Joined::try_from_parts_to_writer(parts.clone(), &mut writer).unwrap();
+
+            assert_eq!(header.encoding, encoding);
+            assert_eq!(header.file_type, FileType::Psbt);
+            assert_eq!(header.num_parts, parts.len());
+
+            assert!(writer.writes.len() > 1, "{encoding:?}");
+            assert_eq!(writer.writes.concat(), data, "{encoding:?}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_join_to_writer_with_limits() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251 + i / 97) as u8).collect();
+        let limits = DecodeLimits {
+            max_decoded_size: Some(10_000),
+            max_ratio: None,
+        };
+
+        for encoding in [Encoding::Hex, Encoding::Zlib] {
+            let parts = split(&data, encoding);
+
+            assert_eq!(
+                Joined::try_from_parts_to_writer_with_limits(parts.clone(), io::sink(), limits),
+                Err(decode::DecodeError::SizeLimitExceeded(10_000).into()),
+                "{encoding:?}"
+            );
+
+            let mut writer = Vec::new();
+            Joined::try_from_parts_to_writer_with_limits(
+                parts,
+                &mut writer,
+                DecodeLimits::unlimited(),
+            )
+            .unwrap();
+            assert_eq!(writer, data, "{encoding:?}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_join_to_writer_errors() {
+        let data = vec![b'A'; 2000];
+        let parts = split(&data, Encoding::Hex);
+
+        let mut missing = parts.clone();
+        missing.remove(1);
+        assert_eq!(
+            Joined::try_from_parts_to_writer(missing, io::sink()),
+            Err(JoinError::MissingPart(1))
+        );
+
+        let mut corrupt = parts.clone();
+        corrupt[2].replace_range(HEADER_LENGTH..HEADER_LENGTH + 2, "ZZ");
+        assert!(matches!(
+            Joined::try_from_parts_to_writer(corrupt, io::sink()),
+            Err(JoinError::DecodeError(
+                decode::DecodeError::UnableToDecodeHex(2, _)
+            ))
+        ));
+
+        struct FailingWriter;
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        assert_eq!(
+            Joined::try_from_parts_to_writer(parts, FailingWriter),
+            Err(JoinError::WriteError("disk full".to_string()))
+        );
+    }
 }
@@ -10,7 +10,7 @@
 //!    encode::Encoding,
 //!    file_type::FileType,
 //!    join::Joined,
-//!    qr::Version,
+//!    qr::{ErrorCorrectionLevel, Version},
 //!    split::{Split, SplitOptions},
 //! };
 //!
@@ -30,6 +30,8 @@
 //!         max_split_number: 100,
 //!         min_version: Version::V03,
 //!         max_version: Version::V30,
+//!         balanced: false,
+//!         error_correction: ErrorCorrectionLevel::Low,
 //!     },
 //! ).expect("Failed to split data");
 //!
@@ -86,13 +88,51 @@
 //! }
 //!
 //! ```
+//!
+//! ### Without the standard library
+//!
+//! Turn off the default features to build with just `alloc`, for firmware. Splitting with Zlib
+//! then falls back to Base32, joining Zlib parts still works.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+// the cdylib and staticlib builds need a panic handler and an allocator, on targets with an
+// operating system they come from std, even with the `std` feature turned off
+#[cfg(all(not(feature = "std"), not(target_os = "none")))]
+extern crate std;
+
+#[cfg(any(feature = "png", feature = "gif"))]
+pub mod animation;
 pub mod consts;
 pub mod continuous_join;
 pub mod decode;
 pub mod encode;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod file_type;
+pub mod fixed_join;
 pub mod header;
+#[cfg(feature = "qr-codes")]
+pub mod html;
 pub mod join;
+pub mod part;
+#[cfg(feature = "python")]
+mod python;
 pub mod qr;
+#[cfg(feature = "qr-codes")]
+pub mod render;
+#[cfg(feature = "scan")]
+pub mod scan;
 pub mod split;
+#[cfg(feature = "std")]
+pub mod streaming_split;
+#[cfg(feature = "qr-codes")]
+pub mod terminal;
+#[cfg(feature = "uniffi")]
+pub mod uniffi_bindings;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
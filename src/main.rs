@@ -0,0 +1,416 @@
+//! The `bbqr` command line tool, enabled with the `cli` feature
+//!
+//! `split` data into parts, `join` parts back into the data, and `inspect` a list of scanned parts.
+//! `split --show` plays the QR codes in the terminal instead of printing the parts
+
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+use bbqr::{
+    consts::MAX_PARTS,
+    decode::DecodeLimits,
+    encode::Encoding,
+    file_type::FileType,
+    join::{JoinMode, Joined},
+    part::Part,
+    qr::{ErrorCorrectionLevel, Version},
+    split::{Split, SplitOptions},
+    terminal::{TerminalAnimationOptions, TerminalMode, TerminalOptions},
+};
+use clap::{Parser, Subcommand, ValueEnum};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "bbqr",
+    version,
+    about = "Split, join and inspect BBQr QR codes"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Split a file into parts, printed one per line
+    Split {
+        /// The file to split, reads from stdin if not given or `-`
+        input: Option<PathBuf>,
+
+        /// The file type of the data
+        #[arg(short = 't', long = "type", value_enum, default_value_t = FileTypeArg::Psbt)]
+        file_type: FileTypeArg,
+
+        /// The encoding to use, zlib falls back to base32 if it doesn't compress, auto picks the
+        /// one that needs the fewest parts
+        #[arg(short, long, value_enum, default_value_t = EncodingArg::Zlib)]
+        encoding: EncodingArg,
+
+        /// The minimum QR version to use
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=40))]
+        min_version: u8,
+
+        /// The maximum QR version to use
+        #[arg(long, default_value_t = 40, value_parser = clap::value_parser!(u8).range(1..=40))]
+        max_version: u8,
+
+        /// The minimum number of parts to split into
+        #[arg(long, default_value_t = 1)]
+        min_parts: usize,
+
+        /// The maximum number of parts to split into
+        #[arg(long, default_value_t = MAX_PARTS)]
+        max_parts: usize,
+
+        /// Spread the data evenly over the parts, instead of leaving a small last part
+        #[arg(long)]
+        balanced: bool,
+
+        /// The QR error correction level, higher levels need more or larger QR codes
+        #[arg(long, value_enum, default_value_t = ErrorCorrectionArg::Low)]
+        error_correction: ErrorCorrectionArg,
+
+        /// Write the parts to a file instead of stdout
+        #[arg(short, long, conflicts_with = "show")]
+        output: Option<PathBuf>,
+
+        /// Show the QR codes animated in the terminal instead of printing the parts
+        #[arg(long)]
+        show: bool,
+
+        /// How long each QR code is shown with `--show`, in milliseconds
+        #[arg(long, default_value_t = 500, requires = "show")]
+        delay: u64,
+
+        /// How many times `--show` plays the QR codes, 0 loops until interrupted
+        #[arg(long, default_value_t = 0, requires = "show")]
+        loops: u16,
+
+        /// Draw the light modules with `--show`, for terminals with a dark background
+        #[arg(long, requires = "show")]
+        invert: bool,
+    },
+
+    /// Join parts, one per line, and write the raw data
+    Join {
+        /// The file with the parts, reads from stdin if not given or `-`
+        input: Option<PathBuf>,
+
+        /// Write the data to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// The most bytes the joined data can decode to, 0 for no limit
+        #[arg(long, default_value_t = default_max_size())]
+        max_size: usize,
+    },
+
+    /// Print the header, and the received and missing parts of a list of parts
+    Inspect {
+        /// The file with the parts, reads from stdin if not given or `-`
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum FileTypeArg {
+    #[value(alias = "P")]
+    Psbt,
+    #[value(alias = "T")]
+    Transaction,
+    #[value(alias = "J")]
+    Json,
+    #[value(alias = "C")]
+    Cbor,
+    #[value(alias = "U")]
+    UnicodeText,
+    #[value(alias = "X")]
+    Experimental,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum EncodingArg {
+    #[value(alias = "H")]
+    Hex,
+    #[value(alias = "2")]
+    Base32,
+    #[value(alias = "Z")]
+    Zlib,
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ErrorCorrectionArg {
+    #[value(alias = "L")]
+    Low,
+    #[value(alias = "M")]
+    Medium,
+    #[value(alias = "Q")]
+    Quartile,
+    #[value(alias = "H")]
+    High,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Split {
+            input,
+            file_type,
+            encoding,
+            min_version,
+            max_version,
+            min_parts,
+            max_parts,
+            balanced,
+            error_correction,
+            output,
+            show,
+            delay,
+            loops,
+            invert,
+        } => {
+            let data = read_input(input.as_deref())?;
+
+            let options = SplitOptions {
+                encoding: encoding.encoding().unwrap_or(Encoding::Zlib),
+                min_split_number: min_parts,
+                max_split_number: max_parts,
+                min_version: version_from_number(min_version)?,
+                max_version: version_from_number(max_version)?,
+                balanced,
+                error_correction: error_correction.into(),
+            };
+
+            let split = match encoding {
+                EncodingArg::Auto => {
+                    let (split, report) =
+                        Split::try_from_data_auto(&data, file_type.into(), options)?;
+
+                    // keep stdout for the parts
+                    for (encoding, rejection) in &report.rejected {
+                        eprintln!("not using {encoding:?}: {rejection}");
+                    }
+
+                    split
+                }
+                _ => Split::try_from_data(&data, file_type.into(), options)?,
+            };
+
+            if show {
+                let mode = if invert {
+                    TerminalMode::Inverted
+                } else {
+                    TerminalMode::Normal
+                };
+
+                let options = TerminalAnimationOptions {
+                    terminal: TerminalOptions {
+                        mode,
+                        ..Default::default()
+                    },
+                    frame_delay: Duration::from_millis(delay),
+                    loop_count: loops,
+                };
+
+                split.animate_in_terminal(&mut io::stdout().lock(), &options)?;
+                return Ok(());
+            }
+
+            let mut parts = split.parts.join("\n");
+            parts.push('\n');
+
+            write_output(output.as_deref(), parts.as_bytes())
+        }
+
+        Command::Join {
+            input,
+            output,
+            max_size,
+        } => {
+            let parts = read_parts(input.as_deref())?;
+            let limits = DecodeLimits {
+                max_decoded_size: (max_size != 0).then_some(max_size),
+                ..DecodeLimits::unlimited()
+            };
+
+            // write the data as each part is decoded, instead of joining it all in memory
+            match output.as_deref() {
+                Some(path) if path != Path::new("-") => {
+                    let file = io::BufWriter::new(fs::File::create(path)?);
+
+                    // don't leave part of the data behind if the join fails
+                    let joined = Joined::try_from_parts_to_writer_with_limits(parts, file, limits);
+                    if let Err(error) = joined {
+                        let _ = fs::remove_file(path);
+                        return Err(error.into());
+                    }
+                }
+                _ => {
+                    let stdout = io::stdout().lock();
+                    Joined::try_from_parts_to_writer_with_limits(parts, stdout, limits)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        Command::Inspect { input } => {
+            let parts = read_parts(input.as_deref())?;
+            inspect(&parts)
+        }
+    }
+}
+
+fn inspect(parts: &[String]) -> Result<(), Box<dyn Error>> {
+    // show the parts of file types this version doesn't know too
+    let parsed: Vec<Option<Part>> = parts
+        .iter()
+        .map(|part| Part::parse_with_mode(part, JoinMode::Lenient).ok())
+        .collect();
+
+    // the header of the first valid part, the parts of other splits are ignored
+    let header = parsed
+        .iter()
+        .flatten()
+        .map(|part| part.header)
+        .next()
+        .ok_or("no valid parts found")?;
+
+    let mut received = BTreeSet::new();
+    let mut ignored = 0;
+
+    for part in &parsed {
+        match part {
+            Some(part) if part.header == header => {
+                received.insert(part.index);
+            }
+            _ => ignored += 1,
+        }
+    }
+
+    let missing: Vec<String> = (0..header.num_parts)
+        .filter(|index| !received.contains(index))
+        .map(|index| index.to_string())
+        .collect();
+
+    println!("header:    {header}");
+    println!("encoding:  {:?}", header.encoding);
+    println!("file type: {}", header.file_type);
+    println!("parts:     {}", header.num_parts);
+    println!("received:  {} ({} scanned)", received.len(), parts.len());
+
+    if ignored > 0 {
+        println!("ignored:   {ignored}");
+    }
+
+    if missing.is_empty() {
+        println!("missing:   none");
+    } else {
+        println!("missing:   {}", missing.join(", "));
+    }
+
+    Ok(())
+}
+
+fn version_from_number(version_number: u8) -> Result<Version, String> {
+    Version::try_from_number(version_number.into())
+        .ok_or_else(|| format!("invalid QR version {version_number}"))
+}
+
+fn read_input(path: Option<&Path>) -> io::Result<Vec<u8>> {
+    match path {
+        Some(path) if path != Path::new("-") => fs::read(path),
+        _ => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data)?;
+            Ok(data)
+        }
+    }
+}
+
+/// Read the parts one per line, skipping blank lines
+fn read_parts(path: Option<&Path>) -> Result<Vec<String>, Box<dyn Error>> {
+    let input = String::from_utf8(read_input(path)?)?;
+
+    let parts = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect();
+
+    Ok(parts)
+}
+
+fn write_output(path: Option<&Path>, data: &[u8]) -> Result<(), Box<dyn Error>> {
+    match path {
+        Some(path) if path != Path::new("-") => fs::write(path, data)?,
+        _ => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(data)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+impl From<FileTypeArg> for FileType {
+    fn from(file_type: FileTypeArg) -> Self {
+        match file_type {
+            FileTypeArg::Psbt => FileType::Psbt,
+            FileTypeArg::Transaction => FileType::Transaction,
+            FileTypeArg::Json => FileType::Json,
+            FileTypeArg::Cbor => FileType::Cbor,
+            FileTypeArg::UnicodeText => FileType::UnicodeText,
+            FileTypeArg::Experimental => FileType::Experimental,
+        }
+    }
+}
+
+/// The default limit on the joined data, from [`DecodeLimits::default`]
+fn default_max_size() -> usize {
+    DecodeLimits::default().max_decoded_size.unwrap_or(0)
+}
+
+impl From<ErrorCorrectionArg> for ErrorCorrectionLevel {
+    fn from(error_correction: ErrorCorrectionArg) -> Self {
+        match error_correction {
+            ErrorCorrectionArg::Low => ErrorCorrectionLevel::Low,
+            ErrorCorrectionArg::Medium => ErrorCorrectionLevel::Medium,
+            ErrorCorrectionArg::Quartile => ErrorCorrectionLevel::Quartile,
+            ErrorCorrectionArg::High => ErrorCorrectionLevel::High,
+        }
+    }
+}
+
+impl EncodingArg {
+    /// The encoding to split with, `None` to pick it automatically
+    fn encoding(self) -> Option<Encoding> {
+        match self {
+            EncodingArg::Hex => Some(Encoding::Hex),
+            EncodingArg::Base32 => Some(Encoding::Base32),
+            EncodingArg::Zlib => Some(Encoding::Zlib),
+            EncodingArg::Auto => None,
+        }
+    }
+}
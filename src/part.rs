@@ -0,0 +1,154 @@
+//! A single scanned part, parsed into its header, index and data
+//!
+//! Use [`Part::parse`] to cheaply check if a scanned string is a BBQr part, and which one,
+//! before passing it to a joiner
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::{
+    consts::HEADER_LENGTH,
+    encode::Encoding,
+    header::{self, Header, HeaderParseError},
+    join::JoinMode,
+};
+
+/// Errors that can occur when parsing a part
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PartParseError {
+    #[error(transparent)]
+    HeaderParseError(#[from] HeaderParseError),
+
+    #[error("Part index {0} is out of range, the header has {1} parts")]
+    IndexOutOfRange(usize, usize),
+
+    #[error("Part with index {0} has no data")]
+    NoData(usize),
+
+    #[error("Part with index {0} has invalid character {1:?} at position {2}")]
+    InvalidCharacter(usize, char, usize),
+}
+
+/// A single part, borrowing its data from the scanned string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Part<'a> {
+    /// The header, identical in every part of the same split
+    pub header: Header,
+
+    /// The index of this part, starting from 0
+    pub index: usize,
+
+    /// The encoded data, without the header and index
+    pub data: &'a str,
+}
+
+impl Display for Part<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let index = header::int_to_padded_base_36(self.index);
+        write!(f, "{}{index}{}", self.header, self.data)
+    }
+}
+
+impl<'a> Part<'a> {
+    /// Parse a part, checking the header, that the index is in range and that the data only
+    /// uses characters from the encoding's alphabet
+    pub fn parse(part: &'a str) -> Result<Self, PartParseError> {
+        Self::parse_with_mode(part, JoinMode::Strict)
+    }
+
+    /// Parse a part, with [`JoinMode::Lenient`] parts of unknown file types are parsed too, see
+    /// [`Header::try_from_str_with_mode`]
+    pub fn parse_with_mode(part: &'a str, mode: JoinMode) -> Result<Self, PartParseError> {
+        let header = Header::try_from_str_with_mode(part, mode)?;
+        let index = header::part_index(part)?;
+
+        if index >= header.num_parts {
+            return Err(PartParseError::IndexOutOfRange(index, header.num_parts));
+        }
+
+        // the header is ASCII, so this is a char boundary
+        let data = &part[HEADER_LENGTH..];
+        if data.is_empty() {
+            return Err(PartParseError::NoData(index));
+        }
+
+        let part = Self {
+            header,
+            index,
+            data,
+        };
+
+        part.validate()?;
+        Ok(part)
+    }
+
+    /// Check that the data only uses characters from the encoding's alphabet
+    pub fn validate(&self) -> Result<(), PartParseError> {
+        let is_valid: fn(char) -> bool = match self.header.encoding {
+            Encoding::Hex => |char: char| matches!(char, '0'..='9' | 'A'..='F'),
+            Encoding::Base32 | Encoding::Zlib => |char: char| matches!(char, 'A'..='Z' | '2'..='7'),
+        };
+
+        match self.data.char_indices().find(|(_, char)| !is_valid(*char)) {
+            Some((position, char)) => {
+                Err(PartParseError::InvalidCharacter(self.index, char, position))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::file_type::FileType;
+
+    #[test]
+    fn test_parse() {
+        let part = Part::parse("B$HU0201FF00").unwrap();
+
+        assert_eq!(
+            part,
+            Part {
+                header: Header::new(Encoding::Hex, FileType::UnicodeText, 2),
+                index: 1,
+                data: "FF00",
+            }
+        );
+        assert_eq!(part.to_string(), "B$HU0201FF00");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Part::parse("B$ZU02!!AAAA"),
+            Err(HeaderParseError::InvalidPartIndex("!!".to_string()).into())
+        );
+        assert_eq!(
+            Part::parse("B$ZU02é"),
+            Err(HeaderParseError::NotAscii.into())
+        );
+        assert_eq!(
+            Part::parse("B$ZU0éAAAA"),
+            Err(HeaderParseError::NotAscii.into())
+        );
+        assert_eq!(
+            Part::parse("B$ZU0"),
+            Err(HeaderParseError::InvalidHeaderSize(5).into())
+        );
+        assert_eq!(
+            Part::parse("B$ZU0202AAAA"),
+            Err(PartParseError::IndexOutOfRange(2, 2))
+        );
+        assert_eq!(Part::parse("B$ZU0201"), Err(PartParseError::NoData(1)));
+        assert_eq!(
+            Part::parse("B$ZU0201AAéA"),
+            Err(PartParseError::InvalidCharacter(1, 'é', 2))
+        );
+        assert_eq!(
+            Part::parse("B$HU0200FF0G"),
+            Err(PartParseError::InvalidCharacter(0, 'G', 3))
+        );
+    }
+}
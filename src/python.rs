@@ -0,0 +1,155 @@
+//! Python extension module with pyo3, enabled with the `python` feature
+//!
+//! Mirrors the reference `bbqr` Python package: `split_qrs` and `join_qrs` take and return the
+//! same arguments, with file types and encodings as single character strings and QR versions as
+//! numbers. `ContinuousJoiner` has no equivalent in the reference package. Errors are raised as
+//! `ValueError`.
+
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+
+use crate::{
+    consts::MAX_PARTS,
+    continuous_join::{self, ContinuousJoinResult},
+    encode::Encoding,
+    file_type::FileType,
+    join::Joined,
+    qr::Version,
+    split::{Split, SplitOptions},
+};
+
+/// Split `raw` into parts, returns the QR version to use and the parts as `(version, parts)`
+#[pyfunction]
+#[pyo3(signature = (
+    raw,
+    type_code,
+    encoding = None,
+    min_split = 1,
+    max_split = MAX_PARTS,
+    min_version = 5,
+    max_version = 40
+))]
+fn split_qrs(
+    raw: &[u8],
+    type_code: &str,
+    encoding: Option<&str>,
+    min_split: usize,
+    max_split: usize,
+    min_version: usize,
+    max_version: usize,
+) -> PyResult<(usize, Vec<String>)> {
+    let file_type = file_type_from_code(type_code)?;
+
+    let encoding = match encoding {
+        Some(encoding) => single_byte(encoding)
+            .and_then(Encoding::from_byte)
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid encoding: {encoding:?}")))?,
+        None => Encoding::Zlib,
+    };
+
+    let options = SplitOptions {
+        encoding,
+        min_split_number: min_split,
+        max_split_number: max_split,
+        min_version: version_from_number(min_version)?,
+        max_version: version_from_number(max_version)?,
+        ..Default::default()
+    };
+
+    let split = Split::try_from_data(raw, file_type, options).map_err(value_error)?;
+
+    Ok((split.version as usize + 1, split.parts))
+}
+
+/// Join all the parts, returns the file type and the decoded data as `(file_type, raw)`
+#[pyfunction]
+fn join_qrs(py: Python<'_>, input_parts: Vec<String>) -> PyResult<(String, Py<PyBytes>)> {
+    let joined = Joined::try_from_parts(input_parts).map_err(value_error)?;
+    Ok(joined_to_py(py, &joined))
+}
+
+/// Join the parts one at a time, as they are scanned
+#[pyclass(module = "bbqr")]
+#[derive(Default)]
+struct ContinuousJoiner {
+    joiner: continuous_join::ContinuousJoiner,
+    parts_left: Option<usize>,
+}
+
+#[pymethods]
+impl ContinuousJoiner {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a part, returns `(file_type, raw)` once all the parts are joined, otherwise `None`
+    fn add_part(
+        &mut self,
+        py: Python<'_>,
+        part: String,
+    ) -> PyResult<Option<(String, Py<PyBytes>)>> {
+        let result = self.joiner.add_part(part).map_err(value_error)?;
+
+        match result {
+            ContinuousJoinResult::NotStarted => Ok(None),
+            ContinuousJoinResult::InProgress { parts_left } => {
+                self.parts_left = Some(parts_left);
+                Ok(None)
+            }
+            ContinuousJoinResult::Complete(joined) => {
+                self.parts_left = Some(0);
+                Ok(Some(joined_to_py(py, &joined)))
+            }
+        }
+    }
+
+    /// The number of parts left to join, `None` until the first valid part is added
+    #[getter]
+    fn parts_left(&self) -> Option<usize> {
+        self.parts_left
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "bbqr")]
+fn bbqr_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(split_qrs, module)?)?;
+    module.add_function(wrap_pyfunction!(join_qrs, module)?)?;
+    module.add_class::<ContinuousJoiner>()?;
+
+    Ok(())
+}
+
+fn file_type_from_code(type_code: &str) -> PyResult<FileType> {
+    single_byte(type_code)
+        .and_then(FileType::from_byte)
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid file type: {type_code:?}")))
+}
+
+fn single_byte(code: &str) -> Option<u8> {
+    match code.as_bytes() {
+        [byte] => Some(*byte),
+        _ => None,
+    }
+}
+
+fn version_from_number(version_number: usize) -> PyResult<Version> {
+    if !(1..=40).contains(&version_number) {
+        return Err(PyValueError::new_err(format!(
+            "Invalid QR version: {version_number}, must be 1 to 40"
+        )));
+    }
+
+    Ok(Version::from_number(version_number))
+}
+
+fn joined_to_py(py: Python<'_>, joined: &Joined) -> (String, Py<PyBytes>) {
+    let file_type = char::from(joined.file_type.as_byte()).to_string();
+    let raw = PyBytes::new(py, &joined.data).unbind();
+
+    (file_type, raw)
+}
+
+fn value_error(error: impl std::error::Error) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
@@ -1,11 +1,13 @@
 //! Module for QR code related types, including the `Version` and `ErrorCorrectionLevel`
-use std::cmp::Ordering;
+use core::cmp::Ordering;
 
 use crate::consts::QR_DATA_CAPACITY;
 
 /// Enum containing all possible `QRCode` versions, taken from fast_qr crate
 /// The version of the QR code, from V01 to V40
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub enum Version {
     /// Version n°01
     V01 = 0,
@@ -90,10 +92,15 @@ pub enum Version {
 }
 
 /// Error Correction Coding has 4 levels, taken from fast_qr crate
-#[derive(Copy, Clone, Debug)]
-#[allow(dead_code)]
+///
+/// Higher levels recover from more damage, like on a printed backup, but hold less data in the
+/// same version
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub enum ErrorCorrectionLevel {
     /// Low, 7%
+    #[default]
     Low,
     /// Medium, 15%
     Medium,
@@ -139,8 +146,13 @@ impl PartialOrd for QrsNeeded {
 }
 
 impl Version {
+    /// The number of alphanumeric characters the version holds, at the Low error correction level
     pub fn data_capacity(&self) -> usize {
-        let error_correction_level = ErrorCorrectionLevel::Low;
+        self.data_capacity_with_ecl(ErrorCorrectionLevel::Low)
+    }
+
+    /// The number of alphanumeric characters the version holds, at `error_correction_level`
+    pub fn data_capacity_with_ecl(&self, error_correction_level: ErrorCorrectionLevel) -> usize {
         let encoding = CapacityTableEncoding::Alphanumeric;
 
         QR_DATA_CAPACITY[*self as usize][error_correction_level as usize][encoding as usize]
@@ -151,6 +163,13 @@ impl Version {
         Self::from_number(version_index + 1)
     }
 
+    /// The version for a QR version number, 1 to 40
+    pub fn try_from_number(version_number: usize) -> Option<Self> {
+        (1..=40)
+            .contains(&version_number)
+            .then(|| Self::from_number(version_number))
+    }
+
     pub(crate) fn from_number(version_number: usize) -> Self {
         match version_number {
             1 => Version::V01,
@@ -250,6 +269,19 @@ impl From<Version> for fast_qr::Version {
     }
 }
 
+/// Convert between our type and fast-qr error correction level type
+#[cfg(feature = "qr-codes")]
+impl From<ErrorCorrectionLevel> for fast_qr::ECL {
+    fn from(error_correction_level: ErrorCorrectionLevel) -> Self {
+        match error_correction_level {
+            ErrorCorrectionLevel::Low => fast_qr::ECL::L,
+            ErrorCorrectionLevel::Medium => fast_qr::ECL::M,
+            ErrorCorrectionLevel::Quartile => fast_qr::ECL::Q,
+            ErrorCorrectionLevel::High => fast_qr::ECL::H,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -269,6 +301,21 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_capacity_with_ecl() {
+        let capacity = |ecl| Version::V40.data_capacity_with_ecl(ecl);
+
+        assert_eq!(capacity(ErrorCorrectionLevel::Low), 4296);
+        assert_eq!(capacity(ErrorCorrectionLevel::Medium), 3391);
+        assert_eq!(capacity(ErrorCorrectionLevel::Quartile), 2420);
+        assert_eq!(capacity(ErrorCorrectionLevel::High), 1852);
+
+        assert_eq!(
+            Version::V01.data_capacity_with_ecl(ErrorCorrectionLevel::High),
+            10
+        );
+    }
+
     #[test]
     fn version_test() {
         let actual = Version::from_number(1);
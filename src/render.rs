@@ -0,0 +1,346 @@
+//! Render the QR codes of a split to images
+//!
+//! PNG images are enabled with the `png` feature, animations are in [`crate::animation`], the
+//! HTML player is in [`crate::html`] and terminal output is in [`crate::terminal`]
+
+#[cfg(feature = "png")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use fast_qr::{qr::QRCodeError, QRCode};
+
+use crate::split::Split;
+
+/// The widest image that is rendered, in pixels, so a large module size or quiet zone fails
+/// instead of trying to allocate gigabytes
+pub const MAX_IMAGE_SIZE: u32 = 16_384;
+
+/// Errors that can occur when rendering QR codes
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("Unable to generate the QR code for part {0}: {1}")]
+    QrCode(usize, QRCodeError),
+
+    #[error("Module size must be at least 1 pixel")]
+    InvalidModuleSize,
+
+    /// The width in pixels, saturated at `u32::MAX`
+    #[error("Image is too large, {0} pixels wide")]
+    ImageTooLarge(u32),
+
+    #[cfg(feature = "png")]
+    #[error("Unable to encode PNG: {0}")]
+    PngEncoding(#[from] png::EncodingError),
+
+    #[cfg(feature = "gif")]
+    #[error("Unable to encode GIF: {0}")]
+    GifEncoding(#[from] gif::EncodingError),
+
+    #[error("Unable to write output: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Render options, has a default implementation but you can customize it.
+///
+/// Set the module size, quiet zone and colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// The size of each QR module in pixels, default: 8. With the quiet zone, the image can be
+    /// at most [`MAX_IMAGE_SIZE`] pixels wide
+    pub module_size: u32,
+    /// The number of light modules around the QR code, default: 4 as in the QR spec
+    pub quiet_zone: u32,
+    /// The RGB colour of the dark modules, default: black
+    pub foreground: [u8; 3],
+    /// The RGB colour of the light modules and the quiet zone, default: white
+    pub background: [u8; 3],
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            module_size: 8,
+            quiet_zone: 4,
+            foreground: [0, 0, 0],
+            background: [255, 255, 255],
+        }
+    }
+}
+
+/// A rendered QR code, one byte per pixel, `1` for dark and `0` for light
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Bitmap {
+    pub(crate) size: u32,
+    pub(crate) pixels: Vec<u8>,
+}
+
+impl Split {
+    /// Render each part to a PNG image, all at the same QR version
+    #[cfg(feature = "png")]
+    pub fn to_pngs(&self, options: &RenderOptions) -> Result<Vec<Vec<u8>>, RenderError> {
+        self.render_bitmaps(options)?
+            .map(|bitmap| encode_png(&bitmap?, options))
+            .collect()
+    }
+
+    /// Render each part to a PNG image and write them to `dir` as a numbered sequence,
+    /// `{file_stem}-1.png` to `{file_stem}-{n}.png`, numbers are zero padded to the same width.
+    ///
+    /// Returns the paths of the written files, in part order. Each part is written as it is
+    /// rendered, so an error leaves the files of the parts before it
+    #[cfg(feature = "png")]
+    pub fn write_pngs(
+        &self,
+        dir: impl AsRef<Path>,
+        file_stem: &str,
+        options: &RenderOptions,
+    ) -> Result<Vec<PathBuf>, RenderError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let width = self.parts.len().to_string().len();
+
+        // each part is rendered, encoded and written before the next, so only one is held
+        let mut paths = Vec::with_capacity(self.parts.len());
+        for (index, bitmap) in self.render_bitmaps(options)?.enumerate() {
+            let png = encode_png(&bitmap?, options)?;
+
+            let path = dir.join(format!("{file_stem}-{:0width$}.png", index + 1));
+            fs::write(&path, png)?;
+
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    /// Render each part to a bitmap as it is needed, so only one is held at a time. An item
+    /// fails if its part does not fit at the split version
+    pub(crate) fn render_bitmaps<'a>(
+        &'a self,
+        options: &'a RenderOptions,
+    ) -> Result<impl Iterator<Item = Result<Bitmap, RenderError>> + 'a, RenderError> {
+        if options.module_size == 0 {
+            return Err(RenderError::InvalidModuleSize);
+        }
+
+        let bitmaps = self.parts.iter().enumerate().map(|(index, part)| {
+            let qr = self.render_qr_code(index, part)?;
+            Bitmap::from_qr_code(&qr, options)
+        });
+
+        Ok(bitmaps)
+    }
+
+    /// Generate the QR code for each part, unlike `generate_qr_codes` this fails if any part
+    /// does not fit at the split version, instead of skipping it
+    pub(crate) fn render_qr_codes(&self) -> Result<Vec<QRCode>, RenderError> {
+        self.parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| self.render_qr_code(index, part))
+            .collect()
+    }
+
+    fn render_qr_code(&self, index: usize, part: &str) -> Result<QRCode, RenderError> {
+        self.generate_qr_code(part)
+            .map_err(|error| RenderError::QrCode(index, error))
+    }
+}
+
+impl Bitmap {
+    fn from_qr_code(qr: &QRCode, options: &RenderOptions) -> Result<Self, RenderError> {
+        let size = image_size(qr.size as u32, options)?;
+
+        let module_size = options.module_size as usize;
+        let quiet_zone = options.quiet_zone as usize;
+        let size = size as usize;
+
+        let mut pixels = vec![0; size * size];
+
+        for y in 0..qr.size {
+            for x in 0..qr.size {
+                if !qr[y][x].value() {
+                    continue;
+                }
+
+                let left = (x + quiet_zone) * module_size;
+                let top = (y + quiet_zone) * module_size;
+
+                for row in top..top + module_size {
+                    pixels[row * size + left..row * size + left + module_size].fill(1);
+                }
+            }
+        }
+
+        Ok(Self {
+            size: size as u32,
+            pixels,
+        })
+    }
+}
+
+/// The width of the image for a QR code of `qr_size` modules, checked before allocating it
+fn image_size(qr_size: u32, options: &RenderOptions) -> Result<u32, RenderError> {
+    let modules = qr_size.saturating_add(options.quiet_zone.saturating_mul(2));
+    let size = modules.saturating_mul(options.module_size);
+
+    if size > MAX_IMAGE_SIZE {
+        return Err(RenderError::ImageTooLarge(size));
+    }
+
+    Ok(size)
+}
+
+#[cfg(feature = "png")]
+fn encode_png(bitmap: &Bitmap, options: &RenderOptions) -> Result<Vec<u8>, RenderError> {
+    let mut png = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut png, bitmap.size, bitmap.size);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette([options.background, options.foreground].concat());
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&bitmap.pixels)?;
+    writer.finish()?;
+
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode::Encoding, file_type::FileType, qr::Version, split::SplitOptions};
+    use pretty_assertions::assert_eq;
+
+    fn split() -> Split {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: Encoding::Hex,
+            max_version: Version::V10,
+            ..Default::default()
+        };
+
+        Split::try_from_data(&data, FileType::UnicodeText, options).unwrap()
+    }
+
+    #[cfg(feature = "png")]
+    fn decode_png(png: &[u8]) -> (u32, Vec<u8>) {
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(info.width, info.height);
+
+        let palette = reader.info().palette.as_ref().unwrap().to_vec();
+        (info.width, palette)
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_to_pngs() {
+        let split = split();
+        let options = RenderOptions {
+            module_size: 3,
+            quiet_zone: 2,
+            foreground: [10, 20, 30],
+            background: [200, 210, 220],
+        };
+
+        let pngs = split.to_pngs(&options).unwrap();
+        assert_eq!(pngs.len(), split.parts.len());
+
+        let modules = (split.version as u32 + 1) * 4 + 17;
+        for png in &pngs {
+            let (size, palette) = decode_png(png);
+            assert_eq!(size, (modules + 2 * 2) * 3);
+            assert_eq!(palette, vec![200, 210, 220, 10, 20, 30]);
+        }
+    }
+
+    #[test]
+    fn test_bitmap_quiet_zone() {
+        let split = split();
+        let options = RenderOptions {
+            module_size: 2,
+            ..Default::default()
+        };
+
+        let bitmap = split
+            .render_bitmaps(&options)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let size = bitmap.size as usize;
+
+        // the quiet zone is light, the top left finder pattern corner is dark
+        assert!(bitmap.pixels[..size * 8].iter().all(|pixel| *pixel == 0));
+        assert_eq!(bitmap.pixels[8 * size + 8], 1);
+        assert_eq!(bitmap.pixels[8 * size + 7], 0);
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn test_write_pngs() {
+        let split = split();
+        let dir = std::env::temp_dir().join(format!("bbqr-render-test-{}", std::process::id()));
+
+        let paths = split
+            .write_pngs(&dir, "part", &RenderOptions::default())
+            .unwrap();
+
+        assert_eq!(paths.len(), split.parts.len());
+        let width = split.parts.len().to_string().len();
+        assert_eq!(paths[0], dir.join(format!("part-{:0width$}.png", 1)));
+        assert!(paths.iter().all(|path| path.exists()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_render_qr_codes() {
+        let mut split = split();
+
+        let qr_codes = split.render_qr_codes().unwrap();
+        assert_eq!(qr_codes.len(), split.parts.len());
+
+        // a part that doesn't fit at the split version is an error, not skipped
+        split.parts[1].push_str(&"A".repeat(1000));
+        let result = split.render_qr_codes();
+        assert!(matches!(result, Err(RenderError::QrCode(1, _))));
+    }
+
+    #[test]
+    fn test_invalid_module_size() {
+        let options = RenderOptions {
+            module_size: 0,
+            ..Default::default()
+        };
+
+        let result = split().render_bitmaps(&options).map(|_| ());
+        assert!(matches!(result, Err(RenderError::InvalidModuleSize)));
+    }
+
+    #[test]
+    fn test_image_too_large() {
+        for (module_size, quiet_zone) in [(u32::MAX, 4), (8, u32::MAX), (1000, 4)] {
+            let options = RenderOptions {
+                module_size,
+                quiet_zone,
+                ..Default::default()
+            };
+
+            let split = split();
+            let result = split.render_bitmaps(&options).unwrap().next().unwrap();
+            assert!(
+                matches!(result, Err(RenderError::ImageTooLarge(size)) if size > MAX_IMAGE_SIZE),
+                "{module_size} {quiet_zone}"
+            );
+        }
+    }
+}
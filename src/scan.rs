@@ -0,0 +1,580 @@
+//! Decode BBQr parts from images of QR codes, enabled with the `scan` feature
+//!
+//! Images can be PNG or JPEG, and can have more than one QR code in them. QR codes that are not
+//! BBQr parts, that don't start with `B$`, are ignored. Animated GIFs and directories of numbered
+//! frames are scanned frame by frame until all the parts are joined, frames that can't be read
+//! and parts that can't be joined are recorded in the report and skipped.
+
+use std::{
+    fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+};
+
+use image::{codecs::gif::GifDecoder, AnimationDecoder, DynamicImage, GrayImage};
+use log::debug;
+
+use crate::{
+    continuous_join::{ContinuousJoinError, ContinuousJoinResult, ContinuousJoiner},
+    header,
+    join::{JoinError, Joined},
+};
+
+/// The image file extensions read from a directory of frames
+const FRAME_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// The fixed start of every BBQr part
+const PART_PREFIX: &str = "B$";
+
+/// Errors that can occur when decoding images
+#[derive(Debug, thiserror::Error)]
+pub enum ScanError {
+    #[error("Unable to decode image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("No BBQr parts found in the images")]
+    NoParts,
+
+    #[error("Unable to read frames: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    JoinError(#[from] JoinError),
+
+    #[error(transparent)]
+    ContinuousJoinError(#[from] ContinuousJoinError),
+}
+
+/// The result of scanning a sequence of frames, an animated GIF or a directory of images
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanReport {
+    /// The result after the last frame that was scanned
+    pub result: ContinuousJoinResult,
+    /// Each frame that was scanned, in order, scanning stops once the join is complete
+    pub frames: Vec<FrameReport>,
+}
+
+/// The BBQr parts found in one frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameReport {
+    /// The position of the frame in the GIF or the sorted directory, starting at 0
+    pub frame: usize,
+    /// The file the frame was read from, only set for a directory of frames
+    pub path: Option<PathBuf>,
+    /// The indices of the parts found in the frame and joined, empty if there were none
+    pub part_indices: Vec<usize>,
+    /// The parts found in the frame that couldn't be joined, and why
+    pub rejected_parts: Vec<(String, ContinuousJoinError)>,
+    /// Why the frame couldn't be read, if it couldn't, it has no parts then
+    pub error: Option<String>,
+}
+
+/// Find all the BBQr parts in a PNG or JPEG image, in no particular order
+pub fn parts_from_image(image: &[u8]) -> Result<Vec<String>, ScanError> {
+    let image = image::load_from_memory(image)?.into_luma8();
+    Ok(parts_from_luma(&image))
+}
+
+/// Find all the BBQr parts in a greyscale image, in no particular order
+pub(crate) fn parts_from_luma(image: &GrayImage) -> Vec<String> {
+    let (width, height) = image.dimensions();
+
+    let mut prepared =
+        rqrr::PreparedImage::prepare_from_greyscale(width as usize, height as usize, |x, y| {
+            image.get_pixel(x as u32, y as u32).0[0]
+        });
+
+    prepared
+        .detect_grids()
+        .iter()
+        .filter_map(|grid| match grid.decode() {
+            Ok((_, content)) => Some(content),
+            Err(error) => {
+                debug!("unable to decode QR code: {error}");
+                None
+            }
+        })
+        .filter(|content| content.starts_with(PART_PREFIX))
+        .collect()
+}
+
+impl Joined {
+    /// Join all the parts found in a set of PNG or JPEG images
+    pub fn try_from_images<I>(images: I) -> Result<Self, ScanError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut parts = Vec::new();
+        for image in images {
+            parts.extend(parts_from_image(image.as_ref())?);
+        }
+
+        if parts.is_empty() {
+            return Err(ScanError::NoParts);
+        }
+
+        Ok(Self::try_from_parts(parts)?)
+    }
+}
+
+impl ContinuousJoiner {
+    /// Add all the parts found in a PNG or JPEG image to the continuous joiner.
+    ///
+    /// Returns the result after the last part, images without any parts don't change it
+    pub fn add_image(&mut self, image: &[u8]) -> Result<ContinuousJoinResult, ScanError> {
+        let parts = parts_from_image(image)?;
+        self.add_parts(parts)
+    }
+
+    /// Add the parts found in each frame of an animated GIF, until all the parts are joined.
+    ///
+    /// A frame that can't be decoded is skipped, but the GIF can't be read any further once two
+    /// frames in a row fail, scanning stops there with the report so far
+    pub fn add_gif(&mut self, gif: &[u8]) -> Result<ScanReport, ScanError> {
+        let decoder = GifDecoder::new(Cursor::new(gif))?;
+        let mut report = self.new_report();
+        let mut last_failed = false;
+
+        for (frame, image) in decoder.into_frames().enumerate() {
+            let image = match image {
+                Ok(image) => DynamicImage::ImageRgba8(image.into_buffer()).into_luma8(),
+                Err(error) => {
+                    skip_frame(&mut report, frame, None, error.into());
+
+                    if last_failed {
+                        break;
+                    }
+
+                    last_failed = true;
+                    continue;
+                }
+            };
+
+            last_failed = false;
+            let parts = parts_from_luma(&image);
+
+            if self.add_frame(&mut report, frame, None, parts) {
+                break;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Add the parts found in each PNG or JPEG image in a directory, until all the parts are
+    /// joined. Frames are read in the order of the numbers in their file names, so `frame-2.png`
+    /// comes before `frame-10.png`, images that can't be read are skipped
+    pub fn add_frame_dir(&mut self, dir: impl AsRef<Path>) -> Result<ScanReport, ScanError> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            let is_frame = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    FRAME_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+                });
+
+            if is_frame && path.is_file() {
+                paths.push(path);
+            }
+        }
+
+        paths.sort_by_cached_key(|path| (frame_number(path), path.clone()));
+
+        let mut report = self.new_report();
+        for (frame, path) in paths.into_iter().enumerate() {
+            let parts = fs::read(&path)
+                .map_err(ScanError::from)
+                .and_then(|image| parts_from_image(&image));
+
+            let parts = match parts {
+                Ok(parts) => parts,
+                Err(error) => {
+                    skip_frame(&mut report, frame, Some(path), error);
+                    continue;
+                }
+            };
+
+            if self.add_frame(&mut report, frame, Some(path), parts) {
+                break;
+            }
+        }
+
+        Ok(report)
+    }
+
+    pub(crate) fn add_parts(
+        &mut self,
+        parts: Vec<String>,
+    ) -> Result<ContinuousJoinResult, ScanError> {
+        let mut result = self.result();
+
+        for part in parts {
+            result = self.add_part(part)?;
+        }
+
+        Ok(result)
+    }
+
+    fn new_report(&self) -> ScanReport {
+        ScanReport {
+            result: self.result(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Add the parts from one frame to the report, returns true once the join is complete
+    fn add_frame(
+        &mut self,
+        report: &mut ScanReport,
+        frame: usize,
+        path: Option<PathBuf>,
+        parts: Vec<String>,
+    ) -> bool {
+        let mut part_indices = Vec::with_capacity(parts.len());
+        let mut rejected_parts = Vec::new();
+
+        for part in parts {
+            // parse before adding, adding takes the part and checks the index is in range
+            let added = header::part_index(&part)
+                .map_err(ContinuousJoinError::from)
+                .and_then(|index| Ok((index, self.add_part(part.clone())?)));
+
+            match added {
+                Ok((index, result)) => {
+                    report.result = result;
+                    part_indices.push(index);
+                }
+                Err(error) => {
+                    debug!("frame {frame}: unable to join part: {error}");
+                    rejected_parts.push((part, error));
+                }
+            }
+        }
+
+        debug!("frame {frame}: found parts {part_indices:?}");
+        report.frames.push(FrameReport {
+            frame,
+            path,
+            part_indices,
+            rejected_parts,
+            error: None,
+        });
+
+        matches!(report.result, ContinuousJoinResult::Complete(_))
+    }
+}
+
+/// Add a frame that couldn't be read to the report
+fn skip_frame(report: &mut ScanReport, frame: usize, path: Option<PathBuf>, error: ScanError) {
+    debug!("frame {frame}: unable to read: {error}");
+    report.frames.push(FrameReport {
+        frame,
+        path,
+        part_indices: Vec::new(),
+        rejected_parts: Vec::new(),
+        error: Some(error.to_string()),
+    });
+}
+
+/// The last number in a frame's file name, frames without a number sort last
+fn frame_number(path: &Path) -> u64 {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+
+    stem.rsplit(|c: char| !c.is_ascii_digit())
+        .find(|digits| !digits.is_empty())
+        .and_then(|digits| digits.parse().ok())
+        .unwrap_or(u64::MAX)
+}
+
+#[cfg(all(test, feature = "qr-codes"))]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        encode::Encoding,
+        file_type::FileType,
+        header::HeaderParseError,
+        qr::{ErrorCorrectionLevel, Version},
+        render::RenderOptions,
+        split::{Split, SplitOptions},
+    };
+    use image::{codecs::gif::GifEncoder, Frame, ImageFormat, Luma, Rgba, RgbaImage};
+    use pretty_assertions::assert_eq;
+
+    fn data() -> Vec<u8> {
+        (0..1500u32).map(|i| (i * 7 + i / 13) as u8).collect()
+    }
+
+    fn split() -> Split {
+        let options = SplitOptions {
+            encoding: Encoding::Zlib,
+            min_split_number: 3,
+            max_version: Version::V10,
+            ..Default::default()
+        };
+
+        Split::try_from_data(&data(), FileType::Psbt, options).unwrap()
+    }
+
+    /// Render the QR codes from `generate_qr_codes` side by side into one image, as PNG bytes
+    fn render_png(split: &Split, parts: &[usize]) -> Vec<u8> {
+        let options = RenderOptions {
+            module_size: 4,
+            ..Default::default()
+        };
+
+        let bitmaps: Vec<_> = split
+            .render_bitmaps(&options)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let size = bitmaps[0].size;
+
+        let image = GrayImage::from_fn(size * parts.len() as u32, size, |x, y| {
+            let bitmap = &bitmaps[parts[(x / size) as usize]];
+            let pixel = bitmap.pixels[(y * size + x % size) as usize];
+
+            Luma([if pixel == 1 { 0 } else { 255 }])
+        });
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        png
+    }
+
+    #[test]
+    fn test_round_trip_one_qr_per_image() {
+        let split = split();
+        assert_eq!(split.generate_qr_codes().unwrap().len(), split.parts.len());
+
+        let images: Vec<Vec<u8>> = (0..split.parts.len())
+            .map(|index| render_png(&split, &[index]))
+            .collect();
+
+        assert_eq!(
+            parts_from_image(&images[0]).unwrap(),
+            vec![split.parts[0].clone()]
+        );
+
+        let joined = Joined::try_from_images(&images).unwrap();
+        assert_eq!(joined.file_type, FileType::Psbt);
+        assert_eq!(joined.data, data());
+    }
+
+    #[test]
+    fn test_round_trip_high_error_correction() {
+        let options = SplitOptions {
+            max_version: Version::V10,
+            error_correction: ErrorCorrectionLevel::High,
+            ..Default::default()
+        };
+
+        let split = Split::try_from_data(&data(), FileType::Psbt, options).unwrap();
+        assert!(split.parts.len() > self::split().parts.len());
+
+        let images: Vec<Vec<u8>> = (0..split.parts.len())
+            .map(|index| render_png(&split, &[index]))
+            .collect();
+
+        let joined = Joined::try_from_images(&images).unwrap();
+        assert_eq!(joined.data, data());
+    }
+
+    #[test]
+    fn test_multiple_qrs_in_one_image() {
+        let split = split();
+        let all: Vec<usize> = (0..split.parts.len()).collect();
+
+        let mut parts = parts_from_image(&render_png(&split, &all)).unwrap();
+        parts.sort();
+        assert_eq!(parts, split.parts);
+    }
+
+    #[test]
+    fn test_continuous_join_images() {
+        let split = split();
+        let mut joiner = ContinuousJoiner::new();
+
+        let result = joiner.add_image(&render_png(&split, &[1])).unwrap();
+        let parts_left = split.parts.len() - 1;
+        assert_eq!(result, ContinuousJoinResult::InProgress { parts_left });
+
+        let rest: Vec<usize> = (0..split.parts.len()).filter(|index| *index != 1).collect();
+        let result = joiner.add_image(&render_png(&split, &rest)).unwrap();
+
+        let ContinuousJoinResult::Complete(joined) = result else {
+            panic!("expected the join to be complete, got {result:?}");
+        };
+        assert_eq!(joined.data, data());
+    }
+
+    #[test]
+    fn test_add_gif() {
+        let split = split();
+
+        // a blank first frame, every part once, then a repeat that is never scanned
+        let mut frames: Vec<RgbaImage> = (0..split.parts.len())
+            .chain([0])
+            .map(|index| {
+                let png = render_png(&split, &[index]);
+                image::load_from_memory(&png).unwrap().into_rgba8()
+            })
+            .collect();
+
+        let (width, height) = frames[0].dimensions();
+        frames.insert(0, RgbaImage::from_pixel(width, height, Rgba([255; 4])));
+
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            encoder
+                .encode_frames(frames.into_iter().map(Frame::new))
+                .unwrap();
+        }
+
+        let mut joiner = ContinuousJoiner::new();
+        let report = joiner.add_gif(&gif).unwrap();
+
+        let ContinuousJoinResult::Complete(joined) = &report.result else {
+            panic!("expected the join to be complete, got {:?}", report.result);
+        };
+        assert_eq!(joined.data, data());
+
+        assert_eq!(report.frames.len(), split.parts.len() + 1);
+        assert!(report.frames[0].part_indices.is_empty());
+        for (index, frame) in report.frames.iter().skip(1).enumerate() {
+            assert_eq!(frame.frame, index + 1);
+            assert_eq!(frame.path, None);
+            assert_eq!(frame.part_indices, vec![index]);
+        }
+    }
+
+    #[test]
+    fn test_add_frame_dir() {
+        let split = split();
+        let dir = std::env::temp_dir().join(format!("bbqr-scan-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // numbered from 1 without padding, in reverse part order so sorting by name would be wrong
+        let total = split.parts.len();
+        for number in 1..=total {
+            let png = render_png(&split, &[total - number]);
+            fs::write(dir.join(format!("frame-{number}.png")), png).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not a frame").unwrap();
+
+        let mut joiner = ContinuousJoiner::new();
+        let report = joiner.add_frame_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let report = report.unwrap();
+
+        assert!(matches!(report.result, ContinuousJoinResult::Complete(_)));
+        assert_eq!(report.frames.len(), total);
+
+        for (index, frame) in report.frames.iter().enumerate() {
+            let file_name = format!("frame-{}.png", index + 1);
+            assert_eq!(
+                frame.path.as_ref().unwrap().file_name().unwrap(),
+                &*file_name
+            );
+            assert_eq!(frame.part_indices, vec![total - index - 1]);
+        }
+    }
+
+    #[test]
+    fn test_add_frame_dir_skips_bad_frames() {
+        let split = split();
+        let dir = std::env::temp_dir().join(format!("bbqr-scan-bad-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // an unknown file type, that a strict join rejects
+        let unknown = Split {
+            version: Version::V02,
+            parts: vec!["B$HQ0100FF".to_string()],
+            encoding: Encoding::Hex,
+            error_correction: ErrorCorrectionLevel::Low,
+        };
+
+        let total = split.parts.len();
+        fs::write(dir.join("frame-0.png"), "not an image").unwrap();
+        fs::write(dir.join("frame-1.png"), render_png(&unknown, &[0])).unwrap();
+        for index in 0..total {
+            let png = render_png(&split, &[index]);
+            fs::write(dir.join(format!("frame-{}.png", index + 2)), png).unwrap();
+        }
+
+        let mut joiner = ContinuousJoiner::new();
+        let report = joiner.add_frame_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let report = report.unwrap();
+
+        assert!(matches!(report.result, ContinuousJoinResult::Complete(_)));
+        assert_eq!(report.frames.len(), total + 2);
+
+        assert!(report.frames[0].error.is_some());
+        assert!(report.frames[0].part_indices.is_empty());
+
+        assert_eq!(report.frames[1].error, None);
+        assert!(report.frames[1].part_indices.is_empty());
+        assert_eq!(
+            report.frames[1].rejected_parts,
+            vec![(
+                "B$HQ0100FF".to_string(),
+                HeaderParseError::InvalidFileType('Q').into()
+            )]
+        );
+
+        assert_eq!(report.frames[2].part_indices, vec![0]);
+        assert!(report.frames[2].rejected_parts.is_empty());
+    }
+
+    #[test]
+    fn test_frame_number() {
+        assert_eq!(frame_number(Path::new("frame-2.png")), 2);
+        assert_eq!(frame_number(Path::new("bbqr-v2-010.png")), 10);
+        assert_eq!(frame_number(Path::new("cover.png")), u64::MAX);
+    }
+
+    #[test]
+    fn test_ignores_non_bbqr_qrs() {
+        let not_bbqr = Split {
+            version: Version::V02,
+            parts: vec!["HELLO WORLD".to_string()],
+            encoding: Encoding::Hex,
+            error_correction: ErrorCorrectionLevel::Low,
+        };
+
+        assert!(parts_from_image(&render_png(&not_bbqr, &[0]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_no_parts() {
+        let mut png = Vec::new();
+        GrayImage::from_pixel(64, 64, Luma([255]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        assert!(parts_from_image(&png).unwrap().is_empty());
+        assert!(matches!(
+            Joined::try_from_images([&png]),
+            Err(ScanError::NoParts)
+        ));
+        assert!(matches!(
+            parts_from_image(b"not an image"),
+            Err(ScanError::Image(_))
+        ));
+    }
+}
@@ -1,15 +1,23 @@
 //! Split data into multiple parts for QR codes
 
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Display, Formatter};
+
 use crate::{
     consts::MAX_PARTS,
-    encode::{EncodeError, Encoded, Encoding},
+    encode::{self, EncodeError, Encoded, Encoding},
     file_type::FileType,
     header::{int_to_padded_base_36, Header},
-    qr::{QrsNeeded, Version},
+    qr::{ErrorCorrectionLevel, QrsNeeded, Version},
 };
 
 /// Errors that can occur when splitting data
 #[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
 pub enum SplitError {
     #[error("No data found")]
     Empty,
@@ -29,16 +37,22 @@ pub enum SplitError {
     #[error("Invalid version min and max range, min is larger than max")]
     InvalidVersionRange,
 
+    #[error("Unable to read data: {0}")]
+    ReadError(String),
+
     #[error(transparent)]
     EncodeError(#[from] EncodeError),
 }
 
-/// The split Data structure, includes the version, parts, and encoding
+/// The split Data structure, includes the version, parts, encoding and error correction level
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Split {
     pub version: Version,
     pub parts: Vec<String>,
     pub encoding: Encoding,
+    /// The error correction level the parts were sized for, the QR codes must use it too
+    pub error_correction: ErrorCorrectionLevel,
 }
 
 /// Split options, has a default implementation but you can customize it.
@@ -56,6 +70,78 @@ pub struct SplitOptions {
     pub min_version: Version,
     /// The maximum QR version to split to, default: V40
     pub max_version: Version,
+    /// Spread the data evenly over the parts, instead of filling every part and leaving a small
+    /// last part, which is harder to scan, default: false
+    pub balanced: bool,
+    /// The QR error correction level, higher levels need more or larger QR codes, default: Low
+    pub error_correction: ErrorCorrectionLevel,
+}
+
+/// How data would be split, worked out without making the parts, like to show "7 parts at
+/// version 27" before splitting. Sizes are in characters of encoded data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPlan {
+    /// The encoding used, Zlib falls back to Base32 if it doesn't compress
+    pub encoding: Encoding,
+    /// The version of all the QR codes
+    pub version: Version,
+    /// The number of parts
+    pub num_parts: usize,
+    /// The size of the data in every part but the last
+    pub part_size: usize,
+    /// The size of the data in the last part, at most `part_size`
+    pub last_part_size: usize,
+    /// The size of all the encoded data
+    pub encoded_len: usize,
+    /// Every version in the options range that fits the data in an allowed number of parts,
+    /// from the smallest version. The plan uses the one with the fewest parts
+    pub candidates: Vec<SplitCandidate>,
+}
+
+/// A version that fits the data, and the number of parts it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitCandidate {
+    pub version: Version,
+    pub num_parts: usize,
+    /// The size of the data in every part but the last
+    pub part_size: usize,
+}
+
+/// The encoding picked by [`Split::try_from_data_auto`], and why the others were not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingReport {
+    /// The encoding of the split
+    pub chosen: Encoding,
+    /// The other encodings, in the order they were tried
+    pub rejected: Vec<(Encoding, EncodingRejection)>,
+}
+
+/// Why an encoding was not picked by [`Split::try_from_data_auto`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingRejection {
+    /// Compressing didn't make the data smaller, or isn't available without `std`
+    NotCompressible,
+
+    /// The data doesn't fit in the allowed versions and number of parts
+    CannotFit,
+
+    /// The data fits, but in more parts, or as many parts at a larger version, than the chosen
+    /// encoding. On a tie the encoding tried first is picked
+    NotBest { count: usize, version: Version },
+}
+
+impl Display for EncodingRejection {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::NotCompressible => write!(f, "compressing doesn't make the data smaller"),
+            Self::CannotFit => write!(f, "doesn't fit in the allowed versions and parts"),
+            Self::NotBest { count, version } => {
+                let plural = if *count == 1 { "" } else { "s" };
+                let version = *version as usize + 1;
+                write!(f, "needs {count} part{plural} at version {version}")
+            }
+        }
+    }
 }
 
 impl Default for SplitOptions {
@@ -66,6 +152,8 @@ impl Default for SplitOptions {
             max_split_number: 1295,
             min_version: Version::V01,
             max_version: Version::V40,
+            balanced: false,
+            error_correction: ErrorCorrectionLevel::Low,
         }
     }
 }
@@ -79,30 +167,83 @@ impl Split {
         split_qrs(bytes, file_type, options)
     }
 
+    /// Split the data with the encoding that needs the fewest parts, then the smallest version,
+    /// instead of `options.encoding`. Zlib, Base32 and Hex are tried in that order, the report
+    /// says why the others were not picked
+    pub fn try_from_data_auto(
+        bytes: &[u8],
+        file_type: FileType,
+        options: SplitOptions,
+    ) -> Result<(Self, EncodingReport), SplitError> {
+        split_qrs_auto(bytes, file_type, options)
+    }
+
     #[cfg(feature = "qr-codes")]
     pub fn generate_qr_codes(&self) -> Result<Vec<fast_qr::QRCode>, fast_qr::qr::QRCodeError> {
-        use fast_qr::{qr::QRCodeError, QRBuilder, QRCode, Version, ECL};
-
-        // convert version to fast_qr version
-        let version = Version::from(self.version);
-
         let qrs = self
             .parts
             .iter()
-            .map(|part| {
-                let qr: QRCode = QRBuilder::new(part.as_bytes())
-                    .ecl(ECL::L)
-                    .version(version)
-                    .mode(fast_qr::Mode::Alphanumeric)
-                    .build()?;
-
-                Ok::<QRCode, QRCodeError>(qr)
-            })
+            .map(|part| self.generate_qr_code(part))
             .filter_map(Result::ok)
             .collect();
 
         Ok(qrs)
     }
+
+    /// Generate the QR code for one part, at the version of the split
+    #[cfg(feature = "qr-codes")]
+    pub(crate) fn generate_qr_code(
+        &self,
+        part: &str,
+    ) -> Result<fast_qr::QRCode, fast_qr::qr::QRCodeError> {
+        use fast_qr::{QRBuilder, Version, ECL};
+
+        // convert version and error correction level to fast_qr types
+        let version = Version::from(self.version);
+        let ecl = ECL::from(self.error_correction);
+
+        QRBuilder::new(part.as_bytes())
+            .ecl(ecl)
+            .version(version)
+            .mode(fast_qr::Mode::Alphanumeric)
+            .build()
+    }
+}
+
+impl SplitPlan {
+    /// Plan the split of `bytes` with `options`, the data is encoded, and compressed for Zlib,
+    /// but not split into parts
+    pub fn try_from_data(bytes: &[u8], options: SplitOptions) -> Result<Self, SplitError> {
+        if bytes.is_empty() {
+            return Err(SplitError::Empty);
+        }
+
+        options.validate()?;
+
+        let encoded = Encoded::try_new_from_data(bytes, options.encoding)?;
+        let encoded_len = encoded.data.len();
+
+        let candidates: Vec<SplitCandidate> =
+            feasible_versions(encoded.encoding, encoded_len, &options)
+                .map(|qrs_needed| SplitCandidate {
+                    version: qrs_needed.version,
+                    num_parts: qrs_needed.count,
+                    part_size: qrs_needed.data_per_qr,
+                })
+                .collect();
+
+        let best = find_best_version(encoded.encoding, encoded_len, &options)?;
+
+        Ok(Self {
+            encoding: encoded.encoding,
+            version: best.version,
+            num_parts: best.count,
+            part_size: best.data_per_qr,
+            last_part_size: encoded_len - (best.count - 1) * best.data_per_qr,
+            encoded_len,
+            candidates,
+        })
+    }
 }
 
 fn split_qrs(
@@ -118,9 +259,90 @@ fn split_qrs(
     options.validate()?;
 
     let encoded = Encoded::try_new_from_data(bytes, options.encoding)?;
-    let encoded_data_str = encoded.data.as_str();
 
-    let best_version: QrsNeeded = find_best_version(&encoded, &options)?;
+    let best_version: QrsNeeded =
+        find_best_version(encoded.encoding, encoded.data.len(), &options)?;
+
+    Ok(split_encoded(
+        &encoded,
+        best_version,
+        file_type,
+        options.error_correction,
+    ))
+}
+
+fn split_qrs_auto(
+    bytes: &[u8],
+    file_type: FileType,
+    options: SplitOptions,
+) -> Result<(Split, EncodingReport), SplitError> {
+    if bytes.is_empty() {
+        return Err(SplitError::Empty);
+    }
+
+    options.validate()?;
+
+    let mut candidates = Vec::with_capacity(3);
+
+    for encoding in [Encoding::Zlib, Encoding::Base32, Encoding::Hex] {
+        let encoded = Encoded::try_new_from_data(bytes, encoding)?;
+
+        // Zlib falls back to Base32, which is tried next anyway
+        if encoded.encoding != encoding {
+            candidates.push((encoding, Err(EncodingRejection::NotCompressible)));
+            continue;
+        }
+
+        match find_best_version(encoding, encoded.data.len(), &options) {
+            Ok(qrs_needed) => candidates.push((encoding, Ok((encoded, qrs_needed)))),
+            Err(SplitError::CannotFit) => {
+                candidates.push((encoding, Err(EncodingRejection::CannotFit)));
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    // the first of the best is picked on a tie
+    let best_index = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, candidate))| Some((index, &candidate.as_ref().ok()?.1)))
+        .min_by_key(|(_, qrs_needed)| *qrs_needed)
+        .map(|(index, _)| index)
+        .ok_or(SplitError::CannotFit)?;
+
+    let (chosen, best) = candidates.remove(best_index);
+    let (encoded, qrs_needed) = best.expect("only fitting candidates are picked");
+
+    let rejected = candidates
+        .into_iter()
+        .map(|(encoding, candidate)| {
+            let rejection = candidate.map_or_else(
+                |rejection| rejection,
+                |(_, qrs_needed)| EncodingRejection::NotBest {
+                    count: qrs_needed.count,
+                    version: qrs_needed.version,
+                },
+            );
+
+            (encoding, rejection)
+        })
+        .collect();
+
+    let split = split_encoded(&encoded, qrs_needed, file_type, options.error_correction);
+    let report = EncodingReport { chosen, rejected };
+
+    Ok((split, report))
+}
+
+/// Split the encoded data into `best_version.count` parts, with the header and index
+fn split_encoded(
+    encoded: &Encoded,
+    best_version: QrsNeeded,
+    file_type: FileType,
+    error_correction: ErrorCorrectionLevel,
+) -> Split {
+    let encoded_data_str = encoded.data.as_str();
 
     let mut parts = Vec::with_capacity(best_version.count);
     let header_string = Header::new(encoded.encoding, file_type, best_version.count).to_string();
@@ -137,65 +359,79 @@ fn split_qrs(
         parts.push(part);
     }
 
-    let split = Split {
+    Split {
         version: best_version.version,
         parts,
         encoding: encoded.encoding,
-    };
-
-    Ok(split)
+        error_correction,
+    }
 }
 
-fn find_best_version(encoded: &Encoded, options: &SplitOptions) -> Result<QrsNeeded, SplitError> {
-    // keep the best option
-    let mut best_option = None;
-
-    for version_index in options.min_version as usize..=options.max_version as usize {
-        let version = Version::from_index(version_index);
-        let qrs_needed = encoded.number_of_qrs_needed(version);
-
-        let qrs_needed_count = qrs_needed.count;
-
-        // if this option needs more than the max, skip it
-        if qrs_needed_count > MAX_PARTS {
-            continue;
-        };
-
-        // skip if not in the range for min and max split
-        if qrs_needed_count < options.min_split_number
-            || qrs_needed_count > options.max_split_number
-        {
-            continue;
-        }
+/// Find the version that needs the fewest parts, then the smallest version, for `data_len`
+/// characters of encoded data
+pub(crate) fn find_best_version(
+    encoding: Encoding,
+    data_len: usize,
+    options: &SplitOptions,
+) -> Result<QrsNeeded, SplitError> {
+    feasible_versions(encoding, data_len, options)
+        .min()
+        .ok_or(SplitError::CannotFit)
+}
 
-        match &best_option {
-            Some(ref best) => {
-                if &qrs_needed < best {
-                    best_option = Some(qrs_needed);
-                }
-            }
-            None => {
-                best_option = Some(qrs_needed);
+/// Every version in the options range, with the number of parts it needs for `data_len`
+/// characters of encoded data, if that number is in the options range
+fn feasible_versions(
+    encoding: Encoding,
+    data_len: usize,
+    options: &SplitOptions,
+) -> impl Iterator<Item = QrsNeeded> + '_ {
+    (options.min_version as usize..=options.max_version as usize)
+        .filter_map(move |version_index| {
+            let version = Version::from_index(version_index);
+            encode::qrs_needed(encoding, data_len, version, options.error_correction)
+        })
+        .filter(|qrs_needed| {
+            let count = qrs_needed.count;
+
+            // skip if more than the max, or not in the range for min and max split
+            count <= MAX_PARTS
+                && count >= options.min_split_number
+                && count <= options.max_split_number
+        })
+        // sanity check
+        .filter(move |qrs_needed| qrs_needed.data_per_qr * qrs_needed.count >= data_len)
+        .map(move |qrs_needed| {
+            if options.balanced {
+                balance(qrs_needed, encoding, data_len)
+            } else {
+                qrs_needed
             }
-        }
-    }
+        })
+}
 
-    if best_option.is_none() {
-        return Err(SplitError::CannotFit);
+/// Use the smallest part size that still fits the data in the same number of parts, so the
+/// last part is about as large as the others. Every part but the last must be the same size and
+/// a multiple of the split mod, so it can be decoded on its own
+fn balance(qrs_needed: QrsNeeded, encoding: Encoding, data_len: usize) -> QrsNeeded {
+    if qrs_needed.count == 1 {
+        return qrs_needed;
     }
 
-    let best = best_option.expect("just checked");
+    // the full parts are a multiple of the split mod too, so this is never larger than before,
+    // and as the data didn't fit in one part less, the last part is never empty
+    let part_size = data_len
+        .div_ceil(qrs_needed.count)
+        .next_multiple_of(encoding.split_mod());
 
-    // sanity check
-    if best.data_per_qr * best.count < encoded.data.len() {
-        return Err(SplitError::CannotFit);
+    QrsNeeded {
+        data_per_qr: part_size,
+        ..qrs_needed
     }
-
-    Ok(best)
 }
 
 impl SplitOptions {
-    fn validate(&self) -> Result<(), SplitError> {
+    pub(crate) fn validate(&self) -> Result<(), SplitError> {
         if self.max_split_number > MAX_PARTS {
             return Err(SplitError::MaxSplitSizeTooLarge(self.max_split_number));
         }
@@ -219,6 +455,7 @@ impl SplitOptions {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::consts::HEADER_LENGTH;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -233,6 +470,8 @@ mod tests {
                 max_split_number: 1295,
                 min_version: Version::V01,
                 max_version: Version::V40,
+                balanced: false,
+                error_correction: ErrorCorrectionLevel::Low,
             },
         );
 
@@ -266,6 +505,8 @@ mod tests {
                 max_split_number: 1295,
                 min_version: Version::V01,
                 max_version: Version::V40,
+                balanced: false,
+                error_correction: ErrorCorrectionLevel::Low,
             },
         );
 
@@ -285,6 +526,8 @@ mod tests {
                 max_split_number: 1295,
                 min_version: Version::V11,
                 max_version: Version::V40,
+                balanced: false,
+                error_correction: ErrorCorrectionLevel::Low,
             },
         );
 
@@ -305,4 +548,260 @@ mod tests {
         assert_eq!(header.encoding, Encoding::Hex);
         assert_eq!(header.file_type, FileType::Psbt);
     }
+
+    /// Bytes that don't compress
+    fn random_data(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_auto_picks_zlib() {
+        let data = vec![b'A'; 4000];
+        let (split, report) =
+            Split::try_from_data_auto(&data, FileType::UnicodeText, SplitOptions::default())
+                .unwrap();
+
+        assert_eq!(split.encoding, Encoding::Zlib);
+        assert_eq!(report.chosen, Encoding::Zlib);
+        assert_eq!(
+            report.rejected,
+            vec![
+                (
+                    Encoding::Base32,
+                    EncodingRejection::NotBest {
+                        count: 2,
+                        version: Version::V35
+                    }
+                ),
+                (
+                    Encoding::Hex,
+                    EncodingRejection::NotBest {
+                        count: 2,
+                        version: Version::V39
+                    }
+                ),
+            ]
+        );
+
+        let expected = Split::try_from_data(&data, FileType::UnicodeText, Default::default());
+        assert_eq!(split.parts, expected.unwrap().parts);
+    }
+
+    #[test]
+    fn test_auto_skips_zlib_when_not_compressible() {
+        let data = random_data(3000);
+        let options = SplitOptions {
+            max_version: Version::V20,
+            ..Default::default()
+        };
+
+        let (split, report) =
+            Split::try_from_data_auto(&data, FileType::Psbt, options.clone()).unwrap();
+
+        assert_eq!(report.chosen, Encoding::Base32);
+        assert_eq!(
+            report.rejected[0],
+            (Encoding::Zlib, EncodingRejection::NotCompressible)
+        );
+        assert!(matches!(
+            report.rejected[1],
+            (Encoding::Hex, EncodingRejection::NotBest { count, .. }) if count > split.parts.len()
+        ));
+
+        // fits as Base32, but not as Hex
+        let options = SplitOptions {
+            max_split_number: split.parts.len(),
+            ..options
+        };
+
+        let (_, report) = Split::try_from_data_auto(&data, FileType::Psbt, options).unwrap();
+        assert_eq!(report.chosen, Encoding::Base32);
+        assert_eq!(
+            report.rejected[1],
+            (Encoding::Hex, EncodingRejection::CannotFit)
+        );
+    }
+
+    #[test]
+    fn test_auto_cannot_fit() {
+        let options = SplitOptions {
+            max_split_number: 1,
+            max_version: Version::V01,
+            ..Default::default()
+        };
+
+        let split = Split::try_from_data_auto(&random_data(100), FileType::Psbt, options);
+        assert_eq!(split.unwrap_err(), SplitError::CannotFit);
+
+        let split = Split::try_from_data_auto(&[], FileType::Psbt, Default::default());
+        assert_eq!(split.unwrap_err(), SplitError::Empty);
+    }
+
+    #[test]
+    fn test_plan() {
+        let data = random_data(3000);
+        let options = SplitOptions {
+            encoding: Encoding::Hex,
+            min_version: Version::V10,
+            max_version: Version::V20,
+            ..Default::default()
+        };
+
+        let plan = SplitPlan::try_from_data(&data, options.clone()).unwrap();
+        let split = Split::try_from_data(&data, FileType::Psbt, options).unwrap();
+
+        assert_eq!(plan.encoding, split.encoding);
+        assert_eq!(plan.version, split.version);
+        assert_eq!(plan.num_parts, split.parts.len());
+        assert_eq!(plan.encoded_len, 6000);
+
+        let data_len = |part: &String| part.len() - HEADER_LENGTH;
+        assert!(split.parts[..plan.num_parts - 1]
+            .iter()
+            .all(|part| data_len(part) == plan.part_size));
+        assert_eq!(data_len(split.parts.last().unwrap()), plan.last_part_size);
+
+        // one candidate for each version, the plan uses the one with the fewest parts
+        assert_eq!(plan.candidates.len(), 11);
+        assert_eq!(plan.candidates[0].version, Version::V10);
+        assert!(plan.candidates.iter().all(|candidate| {
+            candidate.num_parts >= plan.num_parts
+                && candidate.part_size * candidate.num_parts >= plan.encoded_len
+        }));
+        assert!(plan.candidates.contains(&SplitCandidate {
+            version: plan.version,
+            num_parts: plan.num_parts,
+            part_size: plan.part_size,
+        }));
+    }
+
+    #[test]
+    fn test_plan_candidates_in_range() {
+        let data = random_data(3000);
+        let options = SplitOptions {
+            encoding: Encoding::Base32,
+            min_split_number: 3,
+            max_split_number: 4,
+            ..Default::default()
+        };
+
+        let plan = SplitPlan::try_from_data(&data, options).unwrap();
+        assert!(!plan.candidates.is_empty());
+        assert!(plan
+            .candidates
+            .iter()
+            .all(|candidate| (3..=4).contains(&candidate.num_parts)));
+        assert_eq!(plan.num_parts, 3);
+
+        let options = SplitOptions {
+            max_version: Version::V01,
+            max_split_number: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            SplitPlan::try_from_data(&data, options),
+            Err(SplitError::CannotFit)
+        );
+    }
+
+    #[test]
+    fn test_balanced_plan() {
+        let data = random_data(3000);
+        let options = SplitOptions {
+            encoding: Encoding::Hex,
+            max_version: Version::V10,
+            ..Default::default()
+        };
+
+        let full = SplitPlan::try_from_data(&data, options.clone()).unwrap();
+        assert_eq!(
+            (full.num_parts, full.part_size, full.last_part_size),
+            (16, 386, 210)
+        );
+
+        let options = SplitOptions {
+            balanced: true,
+            ..options
+        };
+
+        // 6000 characters over 16 parts is 375 each, rounded up to the Hex split mod
+        let balanced = SplitPlan::try_from_data(&data, options).unwrap();
+        assert_eq!(balanced.version, full.version);
+        assert_eq!(
+            (
+                balanced.num_parts,
+                balanced.part_size,
+                balanced.last_part_size
+            ),
+            (16, 376, 360)
+        );
+    }
+
+    #[test]
+    fn test_error_correction() {
+        let data = random_data(3000);
+        let levels = [
+            ErrorCorrectionLevel::Low,
+            ErrorCorrectionLevel::Medium,
+            ErrorCorrectionLevel::Quartile,
+            ErrorCorrectionLevel::High,
+        ];
+
+        let splits: Vec<Split> = levels
+            .into_iter()
+            .map(|error_correction| {
+                let options = SplitOptions {
+                    max_version: Version::V10,
+                    error_correction,
+                    ..Default::default()
+                };
+
+                Split::try_from_data(&data, FileType::Psbt, options).unwrap()
+            })
+            .collect();
+
+        // every level holds less data, so needs more parts at the same max version
+        for (split, higher) in splits.iter().zip(&splits[1..]) {
+            assert!(higher.parts.len() > split.parts.len());
+        }
+
+        for (split, error_correction) in splits.iter().zip(levels) {
+            assert_eq!(split.error_correction, error_correction);
+
+            #[cfg(feature = "qr-codes")]
+            for part in &split.parts {
+                split.generate_qr_code(part).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_correction_too_small_for_base32() {
+        // V01 at High holds 2 characters after the header, not a whole Base32 split mod
+        let options = SplitOptions {
+            encoding: Encoding::Base32,
+            max_version: Version::V01,
+            error_correction: ErrorCorrectionLevel::High,
+            ..Default::default()
+        };
+
+        let split = Split::try_from_data(b"hi", FileType::UnicodeText, options.clone());
+        assert_eq!(split.unwrap_err(), SplitError::CannotFit);
+
+        let options = SplitOptions {
+            max_version: Version::V02,
+            ..options
+        };
+        let split = Split::try_from_data(b"hi", FileType::UnicodeText, options).unwrap();
+        assert_eq!(split.version, Version::V02);
+    }
 }
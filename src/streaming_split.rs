@@ -0,0 +1,245 @@
+//! Split data read from any `std::io::Read`, making the parts one at a time
+//!
+//! Only the bytes that go in the QR codes are kept in memory, compressed for Zlib, and each part
+//! is encoded when the iterator gets to it, instead of holding the encoded data and every part.
+
+use std::io::Read;
+
+use flate2::{read::ZlibDecoder, Decompress};
+
+use crate::{
+    encode::{self, EncodeError, Encoding},
+    file_type::FileType,
+    header::{int_to_padded_base_36, Header},
+    qr::Version,
+    split::{find_best_version, SplitError, SplitOptions},
+};
+
+/// An iterator over the parts of data read from a `Read`, see [`StreamingSplit::try_from_reader`]
+///
+/// The parts are the same as [`Split::try_from_data`](crate::split::Split::try_from_data) makes
+/// for the same data and options
+#[derive(Debug, Clone)]
+pub struct StreamingSplit {
+    version: Version,
+    encoding: Encoding,
+    header: String,
+    /// The bytes encoded into the parts, compressed for Zlib
+    bytes: Vec<u8>,
+    num_parts: usize,
+    /// The bytes in every part except the last
+    bytes_per_part: usize,
+    next_index: usize,
+}
+
+impl StreamingSplit {
+    /// Read all the data, compressing it as it is read for Zlib, and pick the QR version and
+    /// number of parts. The parts are made as the iterator is advanced
+    pub fn try_from_reader(
+        mut reader: impl Read,
+        file_type: FileType,
+        options: SplitOptions,
+    ) -> Result<Self, SplitError> {
+        options.validate()?;
+
+        let (bytes, encoding) = read_bytes(&mut reader, options.encoding)?;
+        if bytes.is_empty() {
+            return Err(SplitError::Empty);
+        }
+
+        let best_version =
+            find_best_version(encoding, encoding.encoded_len(bytes.len()), &options)?;
+
+        // all but the last part are a multiple of the split mod, so they encode on their own
+        let bytes_per_part = if best_version.count == 1 {
+            bytes.len()
+        } else {
+            best_version.data_per_qr / encoding.split_mod() * encoding.bytes_per_split_mod()
+        };
+
+        Ok(Self {
+            version: best_version.version,
+            encoding,
+            header: Header::new(encoding, file_type, best_version.count).to_string(),
+            bytes,
+            num_parts: best_version.count,
+            bytes_per_part,
+            next_index: 0,
+        })
+    }
+
+    /// The QR version every part fits in
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The encoding used, Base32 if Zlib was asked for but the data does not compress
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The total number of parts, including the ones already returned
+    pub fn num_parts(&self) -> usize {
+        self.num_parts
+    }
+}
+
+impl Iterator for StreamingSplit {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_index >= self.num_parts {
+            return None;
+        }
+
+        let start = (self.next_index * self.bytes_per_part).min(self.bytes.len());
+        let end = (start + self.bytes_per_part).min(self.bytes.len());
+
+        let part = format!(
+            "{}{}{}",
+            self.header,
+            int_to_padded_base_36(self.next_index),
+            self.encoding.encode(&self.bytes[start..end])
+        );
+
+        self.next_index += 1;
+        Some(part)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.num_parts - self.next_index;
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for StreamingSplit {}
+
+/// Read the bytes to encode, and the encoding to use for them
+fn read_bytes(
+    reader: &mut impl Read,
+    encoding: Encoding,
+) -> Result<(Vec<u8>, Encoding), SplitError> {
+    let read_error = |error: std::io::Error| SplitError::ReadError(error.to_string());
+
+    match encoding {
+        Encoding::Hex | Encoding::Base32 => {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).map_err(read_error)?;
+
+            Ok((bytes, encoding))
+        }
+
+        Encoding::Zlib => {
+            let (compressed, read) = encode::compress(reader).map_err(read_error)?;
+
+            if (compressed.len() as u64) < read {
+                return Ok((compressed, Encoding::Zlib));
+            }
+
+            // the data does not compress, use Base32 of the original data like `Split` does,
+            // getting it back from the compressed copy instead of keeping both
+            let decompress = Decompress::new_with_window_bits(false, 10);
+            let mut bytes = Vec::with_capacity(compressed.len());
+
+            ZlibDecoder::new_with_decompress(&compressed[..], decompress)
+                .read_to_end(&mut bytes)
+                .map_err(|error| EncodeError::CompressionError(error.to_string()))?;
+
+            Ok((bytes, Encoding::Base32))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+    use crate::{join::Joined, split::Split};
+    use pretty_assertions::assert_eq;
+
+    fn compressible() -> Vec<u8> {
+        (0..20_000u32).map(|i| (i % 251 + i / 97) as u8).collect()
+    }
+
+    fn random() -> Vec<u8> {
+        (0..3000).map(|_| rand::random()).collect()
+    }
+
+    #[test]
+    fn test_same_parts_as_split() {
+        let cases = [
+            (compressible(), Encoding::Hex),
+            (compressible(), Encoding::Base32),
+            (compressible(), Encoding::Zlib),
+            (random(), Encoding::Zlib),
+            (b"Hello, world!".to_vec(), Encoding::Zlib),
+        ];
+
+        for ((data, encoding), balanced) in cases
+            .into_iter()
+            .flat_map(|case| [(case.clone(), false), (case, true)])
+        {
+            let options = SplitOptions {
+                encoding,
+                max_version: Version::V15,
+                balanced,
+                ..Default::default()
+            };
+
+            let split = Split::try_from_data(&data, FileType::Psbt, options.clone()).unwrap();
+            let streaming =
+                StreamingSplit::try_from_reader(&data[..], FileType::Psbt, options).unwrap();
+
+            assert_eq!(streaming.version(), split.version);
+            assert_eq!(streaming.encoding(), split.encoding);
+            assert_eq!(streaming.num_parts(), split.parts.len());
+            assert_eq!(streaming.len(), split.parts.len());
+
+            let parts: Vec<String> = streaming.collect();
+            assert_eq!(parts, split.parts, "{encoding:?}");
+
+            let joined = Joined::try_from_parts(parts).unwrap();
+            assert_eq!(joined.data, data);
+        }
+    }
+
+    #[test]
+    fn test_random_data_falls_back_to_base32() {
+        let data = random();
+        let streaming =
+            StreamingSplit::try_from_reader(&data[..], FileType::Psbt, SplitOptions::default())
+                .unwrap();
+
+        assert_eq!(streaming.encoding(), Encoding::Base32);
+    }
+
+    #[test]
+    fn test_errors() {
+        let empty =
+            StreamingSplit::try_from_reader(io::empty(), FileType::Psbt, Default::default());
+        assert_eq!(empty.unwrap_err(), SplitError::Empty);
+
+        let options = SplitOptions {
+            min_split_number: 10,
+            max_split_number: 2,
+            ..Default::default()
+        };
+        let invalid = StreamingSplit::try_from_reader(&b"data"[..], FileType::Psbt, options);
+        assert_eq!(invalid.unwrap_err(), SplitError::InvalidSplitRange);
+
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disconnected"))
+            }
+        }
+
+        let failed =
+            StreamingSplit::try_from_reader(FailingReader, FileType::Psbt, Default::default());
+        assert_eq!(
+            failed.unwrap_err(),
+            SplitError::ReadError("disconnected".to_string())
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Render the QR codes of a split as text for a terminal
+//!
+//! Each character is two QR modules stacked vertically, drawn with the Unicode half block
+//! characters, so the QR codes stay square in most terminal fonts.
+
+use std::{io::Write, thread, time::Duration};
+
+use crate::{
+    render::{Bitmap, RenderError, RenderOptions},
+    split::Split,
+};
+
+/// ANSI escape to hide the cursor while animating
+const HIDE_CURSOR: &str = "\x1b[?25l";
+
+/// ANSI escape to show the cursor again
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// Which modules are drawn with the block characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TerminalMode {
+    /// Dark modules are drawn, for terminals with dark text on a light background
+    #[default]
+    Normal,
+    /// Light modules and the quiet zone are drawn, for terminals with light text on a dark
+    /// background
+    Inverted,
+}
+
+/// Terminal rendering options, has a default implementation but you can customize it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalOptions {
+    /// Which modules are drawn with the block characters, default: Normal
+    pub mode: TerminalMode,
+    /// The number of light modules around the QR code, default: 2
+    pub quiet_zone: u32,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        Self {
+            mode: TerminalMode::Normal,
+            quiet_zone: 2,
+        }
+    }
+}
+
+/// Terminal animation options, has a default implementation but you can customize it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalAnimationOptions {
+    /// How to render each frame
+    pub terminal: TerminalOptions,
+    /// How long each frame is shown, default: 500ms
+    pub frame_delay: Duration,
+    /// How many times the animation plays, `0` loops forever, default: 0
+    pub loop_count: u16,
+}
+
+impl Default for TerminalAnimationOptions {
+    fn default() -> Self {
+        Self {
+            terminal: TerminalOptions::default(),
+            frame_delay: Duration::from_millis(500),
+            loop_count: 0,
+        }
+    }
+}
+
+impl Split {
+    /// Render each part as text with half block characters, one line per two module rows
+    pub fn to_terminal_frames(
+        &self,
+        options: &TerminalOptions,
+    ) -> Result<Vec<String>, RenderError> {
+        let render_options = RenderOptions {
+            module_size: 1,
+            quiet_zone: options.quiet_zone,
+            ..Default::default()
+        };
+
+        let frames = self
+            .render_bitmaps(&render_options)?
+            .map(|bitmap| Ok(bitmap_to_text(&bitmap?, options.mode)))
+            .collect();
+
+        frames
+    }
+
+    /// Play the parts in place, redrawing each frame over the last one with ANSI cursor
+    /// control, with a `3 / 12` counter under the QR code.
+    ///
+    /// Blocks until the animation has played `loop_count` times, or forever if it is `0`. The
+    /// cursor is only hidden while playing when `loop_count` is not `0`, an endless loop is
+    /// stopped by interrupting the process, which would leave the cursor hidden
+    pub fn animate_in_terminal(
+        &self,
+        out: &mut impl Write,
+        options: &TerminalAnimationOptions,
+    ) -> Result<(), RenderError> {
+        let frames = self.to_terminal_frames(&options.terminal)?;
+        let total = frames.len();
+
+        let hide_cursor = options.loop_count != 0;
+        if hide_cursor {
+            write!(out, "{HIDE_CURSOR}")?;
+        }
+
+        let result = (|| {
+            let mut plays = 0;
+            let mut first_frame = true;
+
+            while options.loop_count == 0 || plays < options.loop_count {
+                for (index, frame) in frames.iter().enumerate() {
+                    if !first_frame {
+                        // move back up over the last frame and its counter line
+                        write!(out, "\x1b[{}A\r", frame.lines().count() + 1)?;
+                    }
+                    first_frame = false;
+
+                    // clear to the end of the line in case the counter got shorter
+                    writeln!(out, "{frame}{} / {total}\x1b[K", index + 1)?;
+                    out.flush()?;
+
+                    thread::sleep(options.frame_delay);
+                }
+
+                plays += 1;
+            }
+
+            Ok(())
+        })();
+
+        if hide_cursor {
+            write!(out, "{SHOW_CURSOR}")?;
+        }
+        out.flush()?;
+
+        result
+    }
+}
+
+fn bitmap_to_text(bitmap: &Bitmap, mode: TerminalMode) -> String {
+    let size = bitmap.size as usize;
+
+    let is_drawn = |x: usize, y: usize| {
+        // the row past the bottom edge is part of the background
+        let dark = y < size && bitmap.pixels[y * size + x] == 1;
+
+        match mode {
+            TerminalMode::Normal => dark,
+            TerminalMode::Inverted => y < size && !dark,
+        }
+    };
+
+    let mut text = String::with_capacity((size * 3 + 1) * size.div_ceil(2));
+
+    for y in (0..size).step_by(2) {
+        for x in 0..size {
+            let block = match (is_drawn(x, y), is_drawn(x, y + 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            };
+
+            text.push(block);
+        }
+
+        text.push('\n');
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode::Encoding, file_type::FileType, qr::Version, split::SplitOptions};
+    use pretty_assertions::assert_eq;
+
+    fn split() -> Split {
+        let data = vec![b'A'; 2000];
+        let options = SplitOptions {
+            encoding: Encoding::Hex,
+            max_version: Version::V10,
+            ..Default::default()
+        };
+
+        Split::try_from_data(&data, FileType::UnicodeText, options).unwrap()
+    }
+
+    #[test]
+    fn test_to_terminal_frames() {
+        let split = split();
+        let frames = split
+            .to_terminal_frames(&TerminalOptions::default())
+            .unwrap();
+        assert_eq!(frames.len(), split.parts.len());
+
+        let modules = (split.version as usize + 1) * 4 + 17 + 2 * 2;
+        let lines: Vec<&str> = frames[0].lines().collect();
+
+        assert_eq!(lines.len(), modules.div_ceil(2));
+        assert!(lines.iter().all(|line| line.chars().count() == modules));
+
+        // the quiet zone is blank, the finder pattern starts with a full block
+        assert!(lines[0].chars().all(|block| block == ' '));
+        assert_eq!(lines[1].chars().nth(2), Some('█'));
+    }
+
+    #[test]
+    fn test_inverted_mode() {
+        let split = split();
+        let normal = split
+            .to_terminal_frames(&TerminalOptions::default())
+            .unwrap();
+        let inverted = split
+            .to_terminal_frames(&TerminalOptions {
+                mode: TerminalMode::Inverted,
+                quiet_zone: 2,
+            })
+            .unwrap();
+
+        let normal: Vec<char> = normal[0].lines().nth(1).unwrap().chars().collect();
+        let inverted: Vec<char> = inverted[0].lines().nth(1).unwrap().chars().collect();
+
+        assert_eq!(inverted[0], '█');
+        assert_eq!(normal[2], '█');
+        assert_eq!(inverted[2], ' ');
+    }
+
+    #[test]
+    fn test_animate_in_terminal() {
+        let split = split();
+        let options = TerminalAnimationOptions {
+            frame_delay: Duration::ZERO,
+            loop_count: 2,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        split.animate_in_terminal(&mut out, &options).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let total = split.parts.len();
+        assert!(out.starts_with(HIDE_CURSOR));
+        assert!(out.ends_with(SHOW_CURSOR));
+        assert_eq!(out.matches(&format!("\n1 / {total}\x1b[K")).count(), 2);
+        assert_eq!(
+            out.matches("\x1b[").count(),
+            2 + 2 * total + (2 * total - 1)
+        );
+    }
+}
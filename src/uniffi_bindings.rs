@@ -0,0 +1,269 @@
+//! UniFFI bindings for Kotlin and Swift, enabled with the `uniffi` feature
+//!
+//! `Split`, `Encoding`, `Version`, `ErrorCorrectionLevel` and the error types are exported as is.
+//! Types that use `usize`, need interior mutability, or have data in an enum variant that would
+//! make a Kotlin sealed class, have a binding specific version here.
+
+use std::sync::Mutex;
+
+use crate::{
+    continuous_join::{self, ContinuousJoinError},
+    encode::Encoding,
+    file_type,
+    header::HeaderParseError,
+    join::{self, JoinError},
+    qr::{ErrorCorrectionLevel, Version},
+    split::{self, Split, SplitError},
+};
+
+/// Split options, create the defaults with `default_split_options()`
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct SplitOptions {
+    /// The encoding to use for the splits, defaults to Zlib
+    pub encoding: Encoding,
+    /// The minimum number of parts to split to, default: 1
+    pub min_split_number: u16,
+    /// The maximum number of parts to split to, default: 1295
+    pub max_split_number: u16,
+    /// The minimum QR version to split to, default: V01
+    pub min_version: Version,
+    /// The maximum QR version to split to, default: V40
+    pub max_version: Version,
+    /// Spread the data evenly over the parts, instead of leaving a small last part, default: false
+    pub balanced: bool,
+    /// The QR error correction level, default: Low
+    pub error_correction: ErrorCorrectionLevel,
+}
+
+/// The file type, the bindings only join the file types in the spec, so there is no `Other`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum FileType {
+    Psbt,
+    Transaction,
+    Json,
+    Cbor,
+    UnicodeText,
+    /// The spec's `X` type, for experimental formats
+    Experimental,
+}
+
+/// The joined data, with the encoding and file type from the header
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct Joined {
+    pub encoding: Encoding,
+    pub file_type: FileType,
+    pub data: Vec<u8>,
+}
+
+/// The result of adding a part to the continuous joiner
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum ContinuousJoinResult {
+    /// No valid parts have been added yet
+    NotStarted,
+
+    /// Parts have been added, but not all parts have been joined
+    InProgress {
+        /// The number of parts left to join
+        parts_left: u16,
+    },
+
+    /// All parts have been joined
+    Complete { joined: Joined },
+}
+
+/// Which parts have been received, with the encoding and file type from their header
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct JoinProgress {
+    pub encoding: Encoding,
+    pub file_type: FileType,
+    /// The total number of parts
+    pub total: u16,
+    /// The indices of the parts received so far, in order
+    pub received: Vec<u16>,
+    /// The indices of the parts still missing, in order, empty once complete
+    pub missing: Vec<u16>,
+}
+
+/// Continuously join the parts of the QR codes, one part at a time
+#[derive(uniffi::Object)]
+pub struct ContinuousJoiner(Mutex<continuous_join::ContinuousJoiner>);
+
+#[uniffi::export]
+impl ContinuousJoiner {
+    #[uniffi::constructor]
+    pub fn new() -> Self {
+        Self(Mutex::new(continuous_join::ContinuousJoiner::new()))
+    }
+
+    /// Add a part to the continuous joiner
+    pub fn add_part(&self, part: String) -> Result<ContinuousJoinResult, ContinuousJoinError> {
+        let mut joiner = self.0.lock().expect("continuous joiner lock poisoned");
+        let result = joiner.add_part(part)?;
+
+        Ok(result.try_into()?)
+    }
+
+    /// Which parts have been received and which are still missing, `null` until a valid part
+    /// has been added
+    pub fn progress(&self) -> Option<JoinProgress> {
+        let joiner = self.0.lock().expect("continuous joiner lock poisoned");
+        joiner
+            .progress()
+            .and_then(|progress| progress.try_into().ok())
+    }
+}
+
+impl Default for ContinuousJoiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The default split options, Zlib encoding, 1 to 1295 parts, and versions V01 to V40
+#[uniffi::export]
+pub fn default_split_options() -> SplitOptions {
+    split::SplitOptions::default().into()
+}
+
+/// Split data into parts for QR codes
+#[uniffi::export]
+pub fn split_data(
+    data: Vec<u8>,
+    file_type: FileType,
+    options: SplitOptions,
+) -> Result<Split, SplitError> {
+    Split::try_from_data(&data, file_type.into(), options.into())
+}
+
+/// Join all the parts of the QR codes at once
+#[uniffi::export]
+pub fn join_parts(parts: Vec<String>) -> Result<Joined, JoinError> {
+    let joined = join::Joined::try_from_parts(parts)?;
+    Ok(joined.try_into()?)
+}
+
+impl From<split::SplitOptions> for SplitOptions {
+    fn from(options: split::SplitOptions) -> Self {
+        Self {
+            encoding: options.encoding,
+            // validated to be at most MAX_PARTS (1295) when splitting
+            min_split_number: options.min_split_number.try_into().unwrap_or(u16::MAX),
+            max_split_number: options.max_split_number.try_into().unwrap_or(u16::MAX),
+            min_version: options.min_version,
+            max_version: options.max_version,
+            balanced: options.balanced,
+            error_correction: options.error_correction,
+        }
+    }
+}
+
+impl From<SplitOptions> for split::SplitOptions {
+    fn from(options: SplitOptions) -> Self {
+        Self {
+            encoding: options.encoding,
+            min_split_number: options.min_split_number.into(),
+            max_split_number: options.max_split_number.into(),
+            min_version: options.min_version,
+            max_version: options.max_version,
+            balanced: options.balanced,
+            error_correction: options.error_correction,
+        }
+    }
+}
+
+impl TryFrom<continuous_join::ContinuousJoinResult> for ContinuousJoinResult {
+    type Error = HeaderParseError;
+
+    fn try_from(result: continuous_join::ContinuousJoinResult) -> Result<Self, Self::Error> {
+        let result = match result {
+            continuous_join::ContinuousJoinResult::NotStarted => Self::NotStarted,
+            continuous_join::ContinuousJoinResult::InProgress { parts_left } => Self::InProgress {
+                // at most MAX_PARTS (1295)
+                parts_left: parts_left as u16,
+            },
+            continuous_join::ContinuousJoinResult::Complete(joined) => Self::Complete {
+                joined: joined.try_into()?,
+            },
+        };
+
+        Ok(result)
+    }
+}
+
+impl TryFrom<continuous_join::JoinProgress> for JoinProgress {
+    type Error = HeaderParseError;
+
+    fn try_from(progress: continuous_join::JoinProgress) -> Result<Self, Self::Error> {
+        // indices and counts are at most MAX_PARTS (1295)
+        let to_u16 = |indices: Vec<usize>| indices.into_iter().map(|index| index as u16).collect();
+
+        Ok(Self {
+            encoding: progress.header.encoding,
+            file_type: progress.header.file_type.try_into()?,
+            total: progress.total as u16,
+            received: to_u16(progress.received),
+            missing: to_u16(progress.missing),
+        })
+    }
+}
+
+impl From<FileType> for file_type::FileType {
+    fn from(file_type: FileType) -> Self {
+        match file_type {
+            FileType::Psbt => Self::Psbt,
+            FileType::Transaction => Self::Transaction,
+            FileType::Json => Self::Json,
+            FileType::Cbor => Self::Cbor,
+            FileType::UnicodeText => Self::UnicodeText,
+            FileType::Experimental => Self::Experimental,
+        }
+    }
+}
+
+/// Fails for an unknown file type, only made by a lenient join, the bindings join strictly
+impl TryFrom<file_type::FileType> for FileType {
+    type Error = HeaderParseError;
+
+    fn try_from(file_type: file_type::FileType) -> Result<Self, Self::Error> {
+        match file_type {
+            file_type::FileType::Psbt => Ok(Self::Psbt),
+            file_type::FileType::Transaction => Ok(Self::Transaction),
+            file_type::FileType::Json => Ok(Self::Json),
+            file_type::FileType::Cbor => Ok(Self::Cbor),
+            file_type::FileType::UnicodeText => Ok(Self::UnicodeText),
+            file_type::FileType::Experimental => Ok(Self::Experimental),
+            file_type::FileType::Other(byte) => {
+                Err(HeaderParseError::InvalidFileType(byte as char))
+            }
+        }
+    }
+}
+
+impl TryFrom<join::Joined> for Joined {
+    type Error = HeaderParseError;
+
+    fn try_from(joined: join::Joined) -> Result<Self, Self::Error> {
+        Ok(Self {
+            encoding: joined.encoding,
+            file_type: joined.file_type.try_into()?,
+            data: joined.data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_file_type() {
+        let result = FileType::try_from(file_type::FileType::Other(b'Q'));
+        assert!(matches!(
+            result,
+            Err(HeaderParseError::InvalidFileType('Q'))
+        ));
+
+        let result = FileType::try_from(file_type::FileType::Experimental);
+        assert_eq!(result.unwrap(), FileType::Experimental);
+    }
+}
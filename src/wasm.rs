@@ -0,0 +1,336 @@
+//! WebAssembly bindings with `wasm-bindgen`, enabled with the `wasm` feature
+//!
+//! `Encoding`, `Version` and `ErrorCorrectionLevel` are exported as JS enums, the other types
+//! have a JS class or enum here. Errors are thrown as JS `Error`s with the error message.
+
+use fast_qr::convert::svg::SvgBuilder;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    continuous_join,
+    encode::Encoding,
+    file_type, join,
+    qr::{ErrorCorrectionLevel, Version},
+    split,
+};
+
+/// Split options, `new SplitOptions()` has the defaults
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy)]
+pub struct SplitOptions {
+    /// The encoding to use for the splits, defaults to Zlib
+    pub encoding: Encoding,
+    /// The minimum number of parts to split to, default: 1
+    #[wasm_bindgen(js_name = minSplitNumber)]
+    pub min_split_number: u16,
+    /// The maximum number of parts to split to, default: 1295
+    #[wasm_bindgen(js_name = maxSplitNumber)]
+    pub max_split_number: u16,
+    /// The minimum QR version to split to, default: V01
+    #[wasm_bindgen(js_name = minVersion)]
+    pub min_version: Version,
+    /// The maximum QR version to split to, default: V40
+    #[wasm_bindgen(js_name = maxVersion)]
+    pub max_version: Version,
+    /// Spread the data evenly over the parts, instead of leaving a small last part, default: false
+    pub balanced: bool,
+    /// The QR error correction level, default: Low
+    #[wasm_bindgen(js_name = errorCorrection)]
+    pub error_correction: ErrorCorrectionLevel,
+}
+
+/// The file type, JS enums can't hold the code of an unknown file type so it is just `Other`
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    Psbt,
+    Transaction,
+    Json,
+    Cbor,
+    UnicodeText,
+    Experimental,
+    /// A file type this version doesn't know, can't be used to split
+    Other,
+}
+
+/// The split data, the parts and the QR version and encoding they use
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Split(split::Split);
+
+/// The joined data, with the encoding and file type from the header
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Joined(join::Joined);
+
+/// The status of a continuous join
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinStatus {
+    /// No valid parts have been added yet
+    NotStarted,
+    /// Parts have been added, but not all parts have been joined
+    InProgress,
+    /// All parts have been joined
+    Complete,
+}
+
+/// The result of adding a part to the continuous joiner
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ContinuousJoinResult(continuous_join::ContinuousJoinResult);
+
+/// Which parts have been received and which are still missing
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct JoinProgress(continuous_join::JoinProgress);
+
+/// Continuously join the parts of the QR codes, one part at a time
+#[wasm_bindgen]
+pub struct ContinuousJoiner(continuous_join::ContinuousJoiner);
+
+#[wasm_bindgen]
+impl SplitOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        let options = split::SplitOptions::default();
+
+        Self {
+            encoding: options.encoding,
+            // validated to be at most MAX_PARTS (1295) when splitting
+            min_split_number: options.min_split_number as u16,
+            max_split_number: options.max_split_number as u16,
+            min_version: options.min_version,
+            max_version: options.max_version,
+            balanced: options.balanced,
+            error_correction: options.error_correction,
+        }
+    }
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Split {
+    /// Split data into parts for QR codes, uses the default options if none are given
+    #[wasm_bindgen(js_name = fromData)]
+    pub fn from_data(
+        data: &[u8],
+        file_type: FileType,
+        options: Option<SplitOptions>,
+    ) -> Result<Split, JsError> {
+        let file_type = file_type
+            .try_into()
+            .map_err(|_| JsError::new("Unknown file type"))?;
+
+        let options = options.unwrap_or_default().into();
+        let split = split::Split::try_from_data(data, file_type, options)?;
+
+        Ok(Self(split))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> Version {
+        self.0.version
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn encoding(&self) -> Encoding {
+        self.0.encoding
+    }
+
+    /// The error correction level the QR codes are made with
+    #[wasm_bindgen(getter, js_name = errorCorrection)]
+    pub fn error_correction(&self) -> ErrorCorrectionLevel {
+        self.0.error_correction
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn parts(&self) -> Vec<String> {
+        self.0.parts.clone()
+    }
+
+    /// Generate an SVG image for each part, all at the same QR version, throws if a part
+    /// doesn't fit at that version
+    #[wasm_bindgen(js_name = toSvgs)]
+    pub fn to_svgs(&self) -> Result<Vec<String>, JsError> {
+        let qr_codes = self.0.render_qr_codes()?;
+
+        let svg_builder = SvgBuilder::default();
+        let svgs = qr_codes.iter().map(|qr| svg_builder.to_str(qr)).collect();
+
+        Ok(svgs)
+    }
+}
+
+#[wasm_bindgen]
+impl Joined {
+    /// Join all the parts of the QR codes at once
+    #[wasm_bindgen(js_name = fromParts)]
+    pub fn from_parts(parts: Vec<String>) -> Result<Joined, JsError> {
+        let joined = join::Joined::try_from_parts(parts)?;
+        Ok(Self(joined))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn encoding(&self) -> Encoding {
+        self.0.encoding
+    }
+
+    #[wasm_bindgen(getter, js_name = fileType)]
+    pub fn file_type(&self) -> FileType {
+        self.0.file_type.into()
+    }
+
+    /// The joined data, as a `Uint8Array`
+    #[wasm_bindgen(getter)]
+    pub fn data(&self) -> Vec<u8> {
+        self.0.data.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl ContinuousJoinResult {
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> JoinStatus {
+        match &self.0 {
+            continuous_join::ContinuousJoinResult::NotStarted => JoinStatus::NotStarted,
+            continuous_join::ContinuousJoinResult::InProgress { .. } => JoinStatus::InProgress,
+            continuous_join::ContinuousJoinResult::Complete(_) => JoinStatus::Complete,
+        }
+    }
+
+    /// The number of parts left to join, `0` once complete
+    #[wasm_bindgen(getter, js_name = partsLeft)]
+    pub fn parts_left(&self) -> Option<u16> {
+        match &self.0 {
+            continuous_join::ContinuousJoinResult::NotStarted => None,
+            // at most MAX_PARTS (1295)
+            continuous_join::ContinuousJoinResult::InProgress { parts_left } => {
+                Some(*parts_left as u16)
+            }
+            continuous_join::ContinuousJoinResult::Complete(_) => Some(0),
+        }
+    }
+
+    /// The joined data, only set once complete
+    #[wasm_bindgen(getter)]
+    pub fn joined(&self) -> Option<Joined> {
+        match &self.0 {
+            continuous_join::ContinuousJoinResult::Complete(joined) => Some(Joined(joined.clone())),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl JoinProgress {
+    #[wasm_bindgen(getter)]
+    pub fn encoding(&self) -> Encoding {
+        self.0.header.encoding
+    }
+
+    #[wasm_bindgen(getter, js_name = fileType)]
+    pub fn file_type(&self) -> FileType {
+        self.0.header.file_type.into()
+    }
+
+    /// The total number of parts
+    #[wasm_bindgen(getter)]
+    pub fn total(&self) -> u16 {
+        // at most MAX_PARTS (1295)
+        self.0.total as u16
+    }
+
+    /// The indices of the parts received so far, as a `Uint16Array`
+    #[wasm_bindgen(getter)]
+    pub fn received(&self) -> Vec<u16> {
+        indices_to_u16(&self.0.received)
+    }
+
+    /// The indices of the parts still missing, as a `Uint16Array`
+    #[wasm_bindgen(getter)]
+    pub fn missing(&self) -> Vec<u16> {
+        indices_to_u16(&self.0.missing)
+    }
+}
+
+#[wasm_bindgen]
+impl ContinuousJoiner {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self(continuous_join::ContinuousJoiner::new())
+    }
+
+    /// Add a part to the continuous joiner
+    #[wasm_bindgen(js_name = addPart)]
+    pub fn add_part(&mut self, part: String) -> Result<ContinuousJoinResult, JsError> {
+        let result = self.0.add_part(part)?;
+        Ok(ContinuousJoinResult(result))
+    }
+
+    /// Which parts have been received, `undefined` until a valid part has been added
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> Option<JoinProgress> {
+        self.0.progress().map(JoinProgress)
+    }
+}
+
+impl Default for ContinuousJoiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<SplitOptions> for split::SplitOptions {
+    fn from(options: SplitOptions) -> Self {
+        Self {
+            encoding: options.encoding,
+            min_split_number: options.min_split_number.into(),
+            max_split_number: options.max_split_number.into(),
+            min_version: options.min_version,
+            max_version: options.max_version,
+            balanced: options.balanced,
+            error_correction: options.error_correction,
+        }
+    }
+}
+
+impl From<file_type::FileType> for FileType {
+    fn from(file_type: file_type::FileType) -> Self {
+        match file_type {
+            file_type::FileType::Psbt => Self::Psbt,
+            file_type::FileType::Transaction => Self::Transaction,
+            file_type::FileType::Json => Self::Json,
+            file_type::FileType::Cbor => Self::Cbor,
+            file_type::FileType::UnicodeText => Self::UnicodeText,
+            file_type::FileType::Experimental => Self::Experimental,
+            file_type::FileType::Other(_) => Self::Other,
+        }
+    }
+}
+
+impl TryFrom<FileType> for file_type::FileType {
+    type Error = FileType;
+
+    fn try_from(file_type: FileType) -> Result<Self, Self::Error> {
+        match file_type {
+            FileType::Psbt => Ok(Self::Psbt),
+            FileType::Transaction => Ok(Self::Transaction),
+            FileType::Json => Ok(Self::Json),
+            FileType::Cbor => Ok(Self::Cbor),
+            FileType::UnicodeText => Ok(Self::UnicodeText),
+            FileType::Experimental => Ok(Self::Experimental),
+            FileType::Other => Err(file_type),
+        }
+    }
+}
+
+fn indices_to_u16(indices: &[usize]) -> Vec<u16> {
+    // at most MAX_PARTS (1295)
+    indices.iter().map(|index| *index as u16).collect()
+}
@@ -0,0 +1,204 @@
+// C test harness for the bbqr C API, links against the staticlib
+//
+// Built and run by tests/ffi_test.rs, or by hand:
+//   cargo build --features ffi
+//   cc -Iinclude tests/c/bbqr_test.c target/debug/libbbqr.a -Wl,--gc-sections -lz -lpthread -ldl -lm -o bbqr_test
+
+#include <stdio.h>
+#include <string.h>
+
+#include "bbqr.h"
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #cond);                                      \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+#define CHECK_OK(expr)                                                     \
+    do {                                                                   \
+        BbqrErrorCode code_ = (expr);                                      \
+        if (code_ != BBQR_ERROR_CODE_OK) {                                 \
+            fprintf(stderr, "%s:%d: %s failed: %s\n", __FILE__, __LINE__,  \
+                    #expr, bbqr_error_message(code_));                     \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+static int test_split_and_join(void) {
+    unsigned char data[3000];
+    for (size_t i = 0; i < sizeof(data); i++) {
+        data[i] = (unsigned char)(i * 7 + i / 13);
+    }
+
+    BbqrSplitOptions options;
+    CHECK_OK(bbqr_split_options_default(&options));
+    CHECK(options.encoding == 'Z');
+    CHECK(options.error_correction == 'L');
+
+    options.encoding = 'H';
+    options.max_version = 10;
+    options.error_correction = 'Q';
+
+    BbqrSplit *split = NULL;
+    CHECK_OK(bbqr_split_from_data(data, sizeof(data), 'P', &options, &split));
+    CHECK(split != NULL);
+    CHECK(bbqr_split_encoding(split) == 'H');
+    CHECK(bbqr_split_error_correction(split) == 'Q');
+    CHECK(bbqr_split_version(split) <= 10);
+
+    size_t num_parts = bbqr_split_num_parts(split);
+    CHECK(num_parts > 1);
+    CHECK(bbqr_split_part(split, num_parts) == NULL);
+
+    const char *parts[64];
+    CHECK(num_parts <= 64);
+    for (size_t i = 0; i < num_parts; i++) {
+        parts[i] = bbqr_split_part(split, i);
+        CHECK(strncmp(parts[i], "B$HP", 4) == 0);
+    }
+
+    BbqrJoined joined;
+    CHECK_OK(bbqr_join_parts(parts, num_parts, &joined));
+    CHECK(joined.encoding == 'H');
+    CHECK(joined.file_type == 'P');
+    CHECK(joined.len == sizeof(data));
+    CHECK(memcmp(joined.data, data, sizeof(data)) == 0);
+
+    bbqr_joined_free(&joined);
+    CHECK(joined.data == NULL);
+
+    bbqr_split_free(split);
+    return 0;
+}
+
+static int test_continuous_join(void) {
+    const char *text = "Hello from C, this text is split up and joined one by one";
+
+    BbqrSplitOptions options;
+    CHECK_OK(bbqr_split_options_default(&options));
+    options.encoding = '2';
+    options.min_split_number = 3;
+    options.max_version = 2;
+
+    BbqrSplit *split = NULL;
+    CHECK_OK(bbqr_split_from_data((const unsigned char *)text, strlen(text), 'U',
+                                  &options, &split));
+
+    size_t num_parts = bbqr_split_num_parts(split);
+    CHECK(num_parts >= 3);
+
+    BbqrContinuousJoiner *joiner = bbqr_continuous_joiner_new();
+    CHECK(joiner != NULL);
+
+    BbqrJoined joined;
+    CHECK(bbqr_continuous_joiner_joined(joiner, &joined) ==
+          BBQR_ERROR_CODE_NOT_COMPLETE);
+
+    BbqrJoinProgress progress;
+    CHECK_OK(bbqr_continuous_joiner_add_part(joiner, "", &progress));
+    CHECK(progress.status == BBQR_JOIN_STATUS_NOT_STARTED);
+
+    // add the parts in reverse order
+    for (size_t i = num_parts; i > 0; i--) {
+        CHECK_OK(bbqr_continuous_joiner_add_part(joiner, bbqr_split_part(split, i - 1),
+                                                 &progress));
+
+        if (i > 1) {
+            CHECK(progress.status == BBQR_JOIN_STATUS_IN_PROGRESS);
+            CHECK(progress.parts_left == i - 1);
+        }
+    }
+
+    CHECK(progress.status == BBQR_JOIN_STATUS_COMPLETE);
+
+    CHECK_OK(bbqr_continuous_joiner_joined(joiner, &joined));
+    CHECK(joined.file_type == 'U');
+    CHECK(joined.len == strlen(text));
+    CHECK(memcmp(joined.data, text, joined.len) == 0);
+
+    bbqr_joined_free(&joined);
+    bbqr_continuous_joiner_free(joiner);
+    bbqr_split_free(split);
+    return 0;
+}
+
+static int test_errors(void) {
+    BbqrSplit *split = NULL;
+    unsigned char data[] = {1, 2, 3};
+
+    CHECK(bbqr_split_from_data(data, 0, 'P', NULL, &split) ==
+          BBQR_ERROR_CODE_SPLIT_EMPTY);
+    CHECK(bbqr_split_from_data(data, sizeof(data), '?', NULL, &split) ==
+          BBQR_ERROR_CODE_INVALID_FILE_TYPE);
+    CHECK(bbqr_split_from_data(NULL, sizeof(data), 'P', NULL, &split) ==
+          BBQR_ERROR_CODE_NULL_POINTER);
+
+    BbqrSplitOptions options;
+    CHECK_OK(bbqr_split_options_default(&options));
+    options.error_correction = 'X';
+    CHECK(bbqr_split_from_data(data, sizeof(data), 'P', &options, &split) ==
+          BBQR_ERROR_CODE_INVALID_ERROR_CORRECTION);
+
+    options.error_correction = 'H';
+    options.max_version = 41;
+    CHECK(bbqr_split_from_data(data, sizeof(data), 'P', &options, &split) ==
+          BBQR_ERROR_CODE_INVALID_VERSION);
+
+    options.max_version = 1;
+    options.min_version = 2;
+    CHECK(bbqr_split_from_data(data, sizeof(data), 'P', &options, &split) ==
+          BBQR_ERROR_CODE_SPLIT_INVALID_VERSION_RANGE);
+    CHECK(split == NULL);
+
+    BbqrJoined joined;
+    const char *missing[] = {"B$HP0200AA"};
+    CHECK(bbqr_join_parts(missing, 1, &joined) == BBQR_ERROR_CODE_JOIN_MISSING_PART);
+
+    const char *conflicting[] = {"B$HP0200AA", "B$2P0201AA"};
+    CHECK(bbqr_join_parts(conflicting, 2, &joined) ==
+          BBQR_ERROR_CODE_JOIN_CONFLICTING_HEADERS);
+
+    const char *bad_header[] = {"B#HP0100AA"};
+    CHECK(bbqr_join_parts(bad_header, 1, &joined) ==
+          BBQR_ERROR_CODE_HEADER_INVALID_FIXED_HEADER);
+
+    BbqrContinuousJoiner *joiner = bbqr_continuous_joiner_new();
+    BbqrJoinProgress progress;
+    CHECK(bbqr_continuous_joiner_add_part(joiner, "B$QP0100AA", &progress) ==
+          BBQR_ERROR_CODE_HEADER_INVALID_ENCODING);
+    bbqr_continuous_joiner_free(joiner);
+
+    CHECK(strcmp(bbqr_error_message(BBQR_ERROR_CODE_OK), "ok") == 0);
+    CHECK(strcmp(bbqr_error_message(BBQR_ERROR_CODE_DECODE_SIZE_LIMIT_EXCEEDED),
+                 "decoded data is larger than the limit") == 0);
+    CHECK(strcmp(bbqr_error_message(-1), "unknown error") == 0);
+    CHECK(strcmp(bbqr_error_message(9), "unknown error") == 0);
+
+    // freeing null handles is a no-op
+    bbqr_split_free(NULL);
+    bbqr_continuous_joiner_free(NULL);
+    bbqr_joined_free(NULL);
+
+    return 0;
+}
+
+int main(void) {
+    if (test_split_and_join() != 0) {
+        return 1;
+    }
+
+    if (test_continuous_join() != 0) {
+        return 1;
+    }
+
+    if (test_errors() != 0) {
+        return 1;
+    }
+
+    printf("all C API tests passed\n");
+    return 0;
+}
@@ -0,0 +1,217 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::{ErrorKind, Write},
+    process::{Command, Output, Stdio},
+};
+
+use pretty_assertions::assert_eq;
+
+fn bbqr(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bbqr"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run bbqr");
+
+    // bbqr can exit before reading stdin, like on invalid arguments
+    let written = child.stdin.take().expect("stdin").write_all(stdin);
+    if let Err(error) = written {
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe, "failed to write stdin");
+    }
+
+    child.wait_with_output().expect("failed to wait for bbqr")
+}
+
+#[test]
+fn test_split_and_join() {
+    let data = include_bytes!("../test_data/1in10out.psbt");
+
+    let split = bbqr(
+        &[
+            "split",
+            "test_data/1in10out.psbt",
+            "--type",
+            "psbt",
+            "--encoding",
+            "hex",
+            "--max-version",
+            "10",
+            "--min-parts",
+            "2",
+        ],
+        b"",
+    );
+    assert!(split.status.success());
+
+    let parts = String::from_utf8(split.stdout).unwrap();
+    assert!(parts.lines().count() >= 2);
+    assert!(parts.lines().all(|part| part.starts_with("B$HP")));
+
+    let join = bbqr(&["join"], parts.as_bytes());
+    assert!(join.status.success());
+    assert_eq!(join.stdout, data);
+}
+
+#[test]
+fn test_split_from_stdin() {
+    let split = bbqr(&["split", "-", "-t", "U", "-e", "2"], b"hello from stdin");
+    assert!(split.status.success());
+
+    let parts = String::from_utf8(split.stdout).unwrap();
+    assert_eq!(parts.lines().count(), 1);
+    assert!(parts.starts_with("B$2U0100"));
+}
+
+#[test]
+fn test_split_show() {
+    let args = ["split", "-", "--show", "--loops", "1", "--delay", "0"];
+    let split = bbqr(&args, b"hello from stdin");
+    assert!(split.status.success());
+
+    // the cursor is shown again after the last frame
+    let out = String::from_utf8(split.stdout).unwrap();
+    assert!(out.starts_with("\x1b[?25l"));
+    assert!(out.ends_with("1 / 1\x1b[K\n\x1b[?25h"));
+}
+
+#[test]
+fn test_split_auto_encoding() {
+    let split = bbqr(&["split", "-", "-t", "U", "-e", "auto"], &[b'A'; 1000]);
+    assert!(split.status.success());
+
+    let parts = String::from_utf8(split.stdout).unwrap();
+    assert!(parts.starts_with("B$ZU0100"));
+
+    let report = String::from_utf8(split.stderr).unwrap();
+    assert!(report.contains("not using Base32: needs 1 part at version"));
+    assert!(report.contains("not using Hex: needs 1 part at version"));
+}
+
+#[test]
+fn test_split_error_correction() {
+    let split = |error_correction| {
+        let args = ["split", "test_data/1in10out.psbt", "--max-version", "10"];
+        let output = bbqr(
+            &[&args[..], &["--error-correction", error_correction]].concat(),
+            b"",
+        );
+        assert!(output.status.success());
+
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let low = split("low");
+    let high = split("H");
+    assert!(high.lines().count() > low.lines().count());
+
+    let join = bbqr(&["join"], high.as_bytes());
+    assert!(join.status.success());
+    assert_eq!(join.stdout, include_bytes!("../test_data/1in10out.psbt"));
+}
+
+#[test]
+fn test_join_real_scan() {
+    let data = include_bytes!("../test_data/BBQr.md");
+
+    let join = bbqr(&["join", "test_data/real-scan.txt"], b"");
+    assert!(join.status.success());
+    assert_eq!(join.stdout, data);
+}
+
+#[test]
+fn test_inspect() {
+    let inspect = bbqr(&["inspect", "test_data/real-scan.txt"], b"");
+    assert!(inspect.status.success());
+
+    let report = String::from_utf8(inspect.stdout).unwrap();
+    assert!(report.contains("header:    B$ZU08"));
+    assert!(report.contains("parts:     8"));
+    assert!(report.contains("missing:   none"));
+
+    let parts = include_str!("../test_data/real-scan.txt")
+        .lines()
+        .filter(|part| part.starts_with("B$ZU0801") || part.starts_with("B$ZU0803"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let inspect = bbqr(&["inspect"], parts.as_bytes());
+    assert!(inspect.status.success());
+
+    let report = String::from_utf8(inspect.stdout).unwrap();
+    assert!(report.contains("missing:   0, 2, 4, 5, 6, 7"));
+
+    // a signed index and a bad first line are ignored, not counted or fatal
+    let parts = "not a part\nB$HU03+1FF\nB$HU0300FF\nB$HU0302FF\n";
+    let inspect = bbqr(&["inspect"], parts.as_bytes());
+    assert!(inspect.status.success());
+
+    let report = String::from_utf8(inspect.stdout).unwrap();
+    assert!(report.contains("header:    B$HU03"));
+    assert!(report.contains("received:  2 (4 scanned)"));
+    assert!(report.contains("ignored:   2"));
+    assert!(report.contains("missing:   1"));
+}
+
+#[test]
+fn test_errors() {
+    let split = bbqr(&["split", "--max-version", "41"], b"data");
+    assert!(!split.status.success());
+
+    let split = bbqr(
+        &["split", "--min-version", "5", "--max-version", "2"],
+        b"data",
+    );
+    assert!(!split.status.success());
+    assert!(String::from_utf8_lossy(&split.stderr).starts_with("error: "));
+
+    let split = bbqr(&["split", "--delay", "100"], b"data");
+    assert!(!split.status.success());
+
+    let split = bbqr(&["split", "--loops", "1"], b"data");
+    assert!(!split.status.success());
+
+    let join = bbqr(&["join"], b"B$HP0200AA\n");
+    assert!(!join.status.success());
+}
+
+#[test]
+fn test_join_to_file() {
+    let data = include_bytes!("../test_data/BBQr.md");
+    let dir = std::env::temp_dir().join(format!("bbqr-cli-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let output = dir.join("joined.md");
+    let output_arg = output.to_str().unwrap();
+
+    let join = bbqr(&["join", "test_data/real-scan.txt", "-o", output_arg], b"");
+    assert!(join.status.success());
+    assert_eq!(std::fs::read(&output).unwrap(), data);
+
+    // a failed join does not leave a partial file behind
+    std::fs::remove_file(&output).unwrap();
+    let join = bbqr(&["join", "-o", output_arg], b"B$HP0200AA\nB$HP0201ZZ\n");
+    assert!(!join.status.success());
+    assert!(!output.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_join_max_size() {
+    let join = bbqr(
+        &["join", "test_data/real-scan.txt", "--max-size", "100"],
+        b"",
+    );
+    assert!(!join.status.success());
+
+    let error = String::from_utf8(join.stderr).unwrap();
+    assert!(error.contains("larger than the limit of 100 bytes"));
+
+    let join = bbqr(&["join", "test_data/real-scan.txt", "--max-size", "0"], b"");
+    assert!(join.status.success());
+    assert_eq!(join.stdout, include_bytes!("../test_data/BBQr.md"));
+}
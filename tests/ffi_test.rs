@@ -0,0 +1,45 @@
+#![cfg(all(feature = "ffi", unix))]
+
+use std::{env, path::PathBuf, process::Command};
+
+/// Compile tests/c/bbqr_test.c against include/bbqr.h and the staticlib, then run it
+#[test]
+fn test_c_harness() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    // cargo builds the staticlib into the deps directory the test binary lives in
+    let test_exe = env::current_exe().expect("test executable path");
+    let staticlib = test_exe.with_file_name("libbbqr.a");
+    assert!(staticlib.exists(), "missing {}", staticlib.display());
+
+    let harness = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bbqr_c_test");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let status = Command::new(compiler)
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/bbqr_test.c"))
+        .arg(&staticlib)
+        // drop unused code, like the pyo3 module when built with --all-features
+        .arg("-Wl,--gc-sections")
+        // the C zlib flate2 compresses with
+        .args(["-lz", "-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&harness)
+        .status()
+        .expect("failed to run the C compiler");
+
+    assert!(status.success(), "failed to compile the C harness");
+
+    let output = Command::new(&harness)
+        .output()
+        .expect("failed to run the C harness");
+
+    assert!(
+        output.status.success(),
+        "C harness failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,83 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use bbqr::{
+    encode::Encoding,
+    file_type::FileType,
+    fixed_join::{FixedJoinResult, FixedJoiner},
+    split::{Split, SplitOptions},
+};
+
+/// Counts the allocations on each thread, the test harness allocates on its own threads
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn test_fixed_join_does_not_allocate() {
+    let data: Vec<u8> = (0..5000u32).map(|i| (i % 251 + i / 97) as u8).collect();
+
+    for encoding in [Encoding::Hex, Encoding::Base32, Encoding::Zlib] {
+        let options = SplitOptions {
+            encoding,
+            min_split_number: 4,
+            ..Default::default()
+        };
+
+        let parts = Split::try_from_data(&data, FileType::Psbt, options)
+            .unwrap()
+            .parts;
+
+        let mut corrupt = parts[0].clone();
+        corrupt.replace_range(8..16, "AAAAAAAA");
+
+        let invalid = [
+            "B$",
+            "B$XP0100FF",
+            "B$HQ0100FF",
+            "B$HP0!00FF",
+            "B$HP01!!FF",
+            "B$HP0101FF",
+            "B$HP0100",
+            "B$HP0100XYZ",
+        ];
+
+        let mut buffer = [0; 12_000];
+        let before = allocations();
+
+        let mut joiner = FixedJoiner::new(&mut buffer);
+        for part in invalid {
+            assert!(joiner.add_part(part).is_err());
+        }
+
+        for part in parts[1..].iter().chain([&corrupt, &parts[0]]) {
+            // the corrupt part only fails to inflate for Zlib
+            let _ = joiner.add_part(part);
+        }
+
+        assert!(matches!(joiner.result(), FixedJoinResult::Complete(_)));
+        assert_eq!(allocations(), before, "allocated joining {encoding:?}");
+    }
+}
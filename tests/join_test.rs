@@ -1,4 +1,8 @@
-use bbqr::{file_type::FileType, join::Joined};
+use bbqr::{
+    decode::DecodeError,
+    file_type::FileType,
+    join::{JoinError, Joined},
+};
 
 #[test]
 fn test_real_scan() {
@@ -22,3 +26,28 @@ fn test_real_scan() {
     assert!(data.contains("Zlib compressed"));
     assert!(data.contains("PSBT"));
 }
+
+#[test]
+fn test_real_scan_corrupted() {
+    let mut lines: Vec<String> = include_str!("../test_data/real-scan.txt")
+        .lines()
+        .filter(|ln| !ln.is_empty())
+        .map(|ln| ln.to_string())
+        .collect();
+
+    // a scanning error in the data of the second part, after the 8 character header, the
+    // scan has the same part more than once
+    for line in lines.iter_mut().filter(|line| &line[6..8] == "01") {
+        line.replace_range(18..19, "1");
+    }
+
+    let error = Joined::try_from_parts(lines).expect_err("corrupted part joined");
+
+    let JoinError::DecodeError(DecodeError::UnableToDecodeBase32(index, error)) = &error else {
+        panic!("unexpected error: {error:?}");
+    };
+
+    assert_eq!(*index, 1);
+    assert_eq!(error.position, 10);
+    assert!(error.to_string().contains("at 10"));
+}
@@ -0,0 +1,72 @@
+# Tests for the pyo3 extension module, mirrors how the reference `bbqr` package is used
+#
+# Run by tests/python_test.rs, or by hand after `maturin develop --features python`:
+#   python3 tests/python/test_bbqr.py
+
+import unittest
+
+from bbqr import ContinuousJoiner, join_qrs, split_qrs
+
+
+class TestBBQr(unittest.TestCase):
+    def test_split_and_join(self):
+        raw = bytes((i * 7 + i // 13) % 256 for i in range(3000))
+
+        version, parts = split_qrs(raw, "P", encoding="H", max_version=10)
+        self.assertTrue(5 <= version <= 10)
+        self.assertGreater(len(parts), 1)
+        self.assertTrue(all(part.startswith("B$HP") for part in parts))
+
+        file_type, joined = join_qrs(parts)
+        self.assertEqual(file_type, "P")
+        self.assertEqual(joined, raw)
+
+    def test_default_encoding(self):
+        raw = b"Hello from Python " * 100
+
+        version, parts = split_qrs(raw, "U")
+        self.assertGreaterEqual(version, 5)
+        self.assertEqual(parts[0][2], "Z")
+
+        self.assertEqual(join_qrs(parts), ("U", raw))
+
+    def test_continuous_join(self):
+        raw = b"Hello from Python, this text is split up and joined one by one"
+        _, parts = split_qrs(raw, "U", encoding="2", min_split=3, min_version=1, max_version=2)
+        self.assertGreaterEqual(len(parts), 3)
+
+        joiner = ContinuousJoiner()
+        self.assertIsNone(joiner.parts_left)
+
+        for index, part in reversed(list(enumerate(parts))):
+            result = joiner.add_part(part)
+
+            if index > 0:
+                self.assertIsNone(result)
+                self.assertEqual(joiner.parts_left, index)
+            else:
+                self.assertEqual(result, ("U", raw))
+                self.assertEqual(joiner.parts_left, 0)
+
+    def test_errors(self):
+        with self.assertRaises(ValueError):
+            split_qrs(b"", "P")
+
+        with self.assertRaises(ValueError):
+            split_qrs(b"data", "?")
+
+        with self.assertRaises(ValueError):
+            split_qrs(b"data", "P", encoding="Q")
+
+        with self.assertRaises(ValueError):
+            split_qrs(b"data", "P", max_version=41)
+
+        with self.assertRaises(ValueError):
+            join_qrs(["B$HP0200AA"])
+
+        with self.assertRaises(ValueError):
+            ContinuousJoiner().add_part("B$QP0100AA")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,33 @@
+#![cfg(all(feature = "python", target_os = "linux"))]
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+/// Load the cdylib as the `bbqr` extension module and run tests/python/test_bbqr.py
+#[test]
+fn test_python_module() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    // cargo builds the cdylib into the deps directory the test binary lives in
+    let test_exe = env::current_exe().expect("test executable path");
+    let cdylib = test_exe.with_file_name("libbbqr.so");
+    assert!(cdylib.exists(), "missing {}", cdylib.display());
+
+    // python imports the extension module by file name
+    let module_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("python");
+    fs::create_dir_all(&module_dir).expect("failed to create the module directory");
+    fs::copy(&cdylib, module_dir.join("bbqr.so")).expect("failed to copy the cdylib");
+
+    let python = env::var("PYTHON").unwrap_or_else(|_| "python3".to_string());
+
+    let output = Command::new(python)
+        .arg(manifest_dir.join("tests/python/test_bbqr.py"))
+        .env("PYTHONPATH", &module_dir)
+        .output()
+        .expect("failed to run python");
+
+    assert!(
+        output.status.success(),
+        "python tests failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
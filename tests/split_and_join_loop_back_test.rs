@@ -2,7 +2,7 @@ use bbqr::{
     encode::Encoding,
     file_type::FileType,
     join::Joined,
-    qr::Version,
+    qr::{ErrorCorrectionLevel, Version},
     split::{Split, SplitOptions},
 };
 use pretty_assertions::assert_eq;
@@ -112,6 +112,8 @@ fn test_edge27() {
                         max_split_number: 2,
                         min_version: Version::V27,
                         max_version: Version::V27,
+                        balanced: false,
+                        error_correction: ErrorCorrectionLevel::Low,
                     },
                 )
                 .unwrap();
@@ -174,3 +176,65 @@ fn test_maxsize() {
         assert_eq!(joined.data, data);
     }
 }
+
+#[test]
+fn test_balanced() {
+    let encodings = [Encoding::Hex, Encoding::Base32, Encoding::Zlib];
+    let sizes = [10, 1000, 2345, 10_000, 50_000];
+
+    for encoding in encodings {
+        for size in sizes {
+            let data: Vec<u8> = (0..size).map(|_| rand::random::<u8>()).collect();
+
+            for min_split in [1, 3, 7] {
+                let options = SplitOptions {
+                    encoding,
+                    min_split_number: min_split,
+                    max_version: Version::V20,
+                    balanced: true,
+                    ..Default::default()
+                };
+
+                let split = Split::try_from_data(&data, FileType::Psbt, options.clone());
+                let full = Split::try_from_data(
+                    &data,
+                    FileType::Psbt,
+                    SplitOptions {
+                        balanced: false,
+                        ..options
+                    },
+                );
+
+                // small data can't be split into the min number of parts
+                let (split, full) = match (split, full) {
+                    (Ok(split), Ok(full)) => (split, full),
+                    (split, full) => {
+                        assert_eq!(split.unwrap_err(), full.unwrap_err());
+                        continue;
+                    }
+                };
+
+                // the same version and number of parts, only the sizes change
+                assert_eq!(split.version, full.version);
+                assert_eq!(split.parts.len(), full.parts.len());
+
+                let (last, rest) = split.parts.split_last().unwrap();
+                let split_mod = split.encoding.split_mod();
+
+                // the spec needs all but the last part to be the same size
+                if let Some(first) = rest.first() {
+                    assert!(rest.iter().all(|part| part.len() == first.len()));
+                    assert_eq!((first.len() - 8) % split_mod, 0);
+
+                    // the last part is less than a part plus a split mod smaller than the others
+                    let parts = split.parts.len();
+                    assert!(last.len() <= first.len());
+                    assert!(first.len() - last.len() < parts * split_mod);
+                }
+
+                let joined = Joined::try_from_parts(split.parts).unwrap();
+                assert_eq!(joined.data, data);
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use bbqr::{
     encode::Encoding,
     file_type::FileType,
     join::Joined,
-    qr::Version,
+    qr::{ErrorCorrectionLevel, Version},
     split::{Split, SplitOptions},
 };
 
@@ -23,6 +23,8 @@ fn test_real_from_readme() {
                 max_split_number: 1295,
                 min_version: Version::V01,
                 max_version: Version::V40,
+                balanced: false,
+                error_correction: ErrorCorrectionLevel::Low,
             },
         );
 
@@ -36,3 +38,27 @@ fn test_real_from_readme() {
         assert_eq!(String::from_utf8(join_from_readme.data).unwrap(), data);
     }
 }
+
+#[test]
+fn test_zlib_matches_reference() {
+    // the parts of real-scan.txt were split by the Python reference implementation, with
+    // `zlib.compressobj(9, zlib.DEFLATED, -10)`
+    let mut expected: Vec<&str> = include_str!("../test_data/real-scan.txt")
+        .lines()
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    expected.sort();
+    expected.dedup();
+
+    let options = SplitOptions {
+        encoding: Encoding::Zlib,
+        min_split_number: expected.len(),
+        ..Default::default()
+    };
+
+    let data = include_str!("../test_data/BBQr.md");
+    let split = Split::try_from_data(data.as_bytes(), FileType::UnicodeText, options).unwrap();
+
+    assert_eq!(split.parts, expected);
+}
@@ -0,0 +1,77 @@
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+//! Run with `cargo test --target wasm32-unknown-unknown --features wasm`
+
+use bbqr::{
+    encode::Encoding,
+    qr::{ErrorCorrectionLevel, Version},
+    wasm::{ContinuousJoiner, FileType, JoinStatus, Joined, Split, SplitOptions},
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn test_data() -> Vec<u8> {
+    (0..3000u32).map(|i| (i * 7 + i / 13) as u8).collect()
+}
+
+#[wasm_bindgen_test]
+fn test_split_and_join() {
+    let data = test_data();
+
+    let mut options = SplitOptions::new();
+    options.encoding = Encoding::Hex;
+    options.max_version = Version::V10;
+    options.error_correction = ErrorCorrectionLevel::Quartile;
+
+    let split = Split::from_data(&data, FileType::Psbt, Some(options)).unwrap();
+    assert_eq!(split.encoding(), Encoding::Hex);
+    assert_eq!(split.error_correction(), ErrorCorrectionLevel::Quartile);
+    assert!(split.version() <= Version::V10);
+
+    let parts = split.parts();
+    assert!(parts.len() > 1);
+
+    let svgs = split.to_svgs().unwrap();
+    assert_eq!(svgs.len(), parts.len());
+    assert!(svgs[0].starts_with("<svg"));
+
+    let joined = Joined::from_parts(parts).unwrap();
+    assert_eq!(joined.file_type(), FileType::Psbt);
+    assert_eq!(joined.data(), data);
+}
+
+#[wasm_bindgen_test]
+fn test_continuous_join() {
+    let text = b"Hello from wasm, this text is split up and joined one by one";
+
+    let mut options = SplitOptions::new();
+    options.encoding = Encoding::Base32;
+    options.min_split_number = 3;
+    options.max_version = Version::V02;
+
+    let split = Split::from_data(text, FileType::UnicodeText, Some(options)).unwrap();
+    let parts = split.parts();
+    assert!(parts.len() >= 3);
+
+    let mut joiner = ContinuousJoiner::new();
+    let mut status = JoinStatus::NotStarted;
+    assert!(joiner.progress().is_none());
+
+    for (index, part) in parts.iter().enumerate().rev() {
+        let result = joiner.add_part(part.clone()).unwrap();
+        status = result.status();
+
+        let progress = joiner.progress().unwrap();
+        assert_eq!(progress.total() as usize, parts.len());
+        assert_eq!(progress.missing(), (0..index as u16).collect::<Vec<_>>());
+
+        if index > 0 {
+            assert_eq!(status, JoinStatus::InProgress);
+            assert_eq!(result.parts_left(), Some(index as u16));
+            assert!(result.joined().is_none());
+        } else {
+            assert_eq!(result.joined().unwrap().data(), text);
+        }
+    }
+
+    assert_eq!(status, JoinStatus::Complete);
+}
@@ -0,0 +1,11 @@
+//! Generate the Kotlin and Swift bindings for bbqr
+//!
+//! ```sh
+//! cargo build -p bbqr --features uniffi
+//! cargo run -p uniffi-bindgen -- generate --library target/debug/libbbqr.so \
+//!     --language kotlin --language swift --out-dir bindings
+//! ```
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,65 @@
+import uniffi.bbqr.*
+
+val data = "Hello from Kotlin, ".repeat(200).toByteArray()
+
+// split and join all at once
+val options = defaultSplitOptions()
+options.minSplitNumber = 3u
+options.maxVersion = Version.V05
+
+val split = splitData(data, FileType.UNICODE_TEXT, options)
+assert(split.parts.size >= 3)
+assert(split.version <= Version.V05)
+assert(split.encoding == Encoding.ZLIB)
+
+val joined = joinParts(split.parts)
+assert(joined.fileType == FileType.UNICODE_TEXT)
+assert(joined.data.contentEquals(data))
+
+val experimental = splitData(data, FileType.EXPERIMENTAL, defaultSplitOptions())
+assert(joinParts(experimental.parts).fileType == FileType.EXPERIMENTAL)
+
+// join one part at a time, in reverse order
+val joiner = ContinuousJoiner()
+assert(joiner.addPart("") == ContinuousJoinResult.NotStarted)
+
+var result: ContinuousJoinResult = ContinuousJoinResult.NotStarted
+for (part in split.parts.reversed()) {
+    result = joiner.addPart(part)
+}
+
+when (result) {
+    is ContinuousJoinResult.Complete -> assert(result.joined.data.contentEquals(data))
+    else -> throw AssertionError("expected the join to be complete, got $result")
+}
+
+val progress = ContinuousJoiner().addPart(split.parts[0])
+assert(progress == ContinuousJoinResult.InProgress((split.parts.size - 1).toUShort()))
+
+// which parts have been received
+val progressJoiner = ContinuousJoiner()
+assert(progressJoiner.progress() == null)
+progressJoiner.addPart(split.parts[1])
+val joinProgress = progressJoiner.progress()!!
+assert(joinProgress.total.toInt() == split.parts.size)
+assert(joinProgress.received == listOf(1.toUShort()))
+assert(joinProgress.missing.size == split.parts.size - 1)
+
+// errors are mapped to exceptions
+try {
+    splitData(ByteArray(0), FileType.PSBT, defaultSplitOptions())
+    throw AssertionError("should have thrown")
+} catch (e: SplitException.Empty) {
+}
+
+try {
+    joinParts(listOf("B#HP0100AA"))
+    throw AssertionError("should have thrown")
+} catch (e: JoinException.HeaderParseException) {
+}
+
+try {
+    ContinuousJoiner().addPart("B\$QP0200AA")
+    throw AssertionError("should have thrown")
+} catch (e: ContinuousJoinException.HeaderParseException) {
+}
@@ -0,0 +1,65 @@
+import Foundation
+import bbqr
+
+let data = String(repeating: "Hello from Swift, ", count: 200).data(using: .utf8)!
+
+// split and join all at once
+var options = defaultSplitOptions()
+options.minSplitNumber = 3
+options.maxVersion = .v05
+
+let split = try! splitData(data: data, fileType: .unicodeText, options: options)
+assert(split.parts.count >= 3)
+assert(split.encoding == .zlib)
+
+let joined = try! joinParts(parts: split.parts)
+assert(joined.fileType == .unicodeText)
+assert(joined.data == data)
+
+let experimental = try! splitData(data: data, fileType: .experimental, options: defaultSplitOptions())
+assert(try! joinParts(parts: experimental.parts).fileType == .experimental)
+
+// join one part at a time, in reverse order
+let joiner = ContinuousJoiner()
+assert(try! joiner.addPart(part: "") == .notStarted)
+
+var result = ContinuousJoinResult.notStarted
+for part in split.parts.reversed() {
+    result = try! joiner.addPart(part: part)
+}
+
+guard case let .complete(joinedResult) = result else {
+    fatalError("expected the join to be complete, got \(result)")
+}
+assert(joinedResult.data == data)
+
+let progress = try! ContinuousJoiner().addPart(part: split.parts[0])
+assert(progress == .inProgress(partsLeft: UInt16(split.parts.count - 1)))
+
+// which parts have been received
+let progressJoiner = ContinuousJoiner()
+assert(progressJoiner.progress() == nil)
+_ = try! progressJoiner.addPart(part: split.parts[1])
+let joinProgress = progressJoiner.progress()!
+assert(Int(joinProgress.total) == split.parts.count)
+assert(joinProgress.received == [1])
+assert(joinProgress.missing.count == split.parts.count - 1)
+
+// errors are mapped to swift errors
+do {
+    _ = try splitData(data: Data(), fileType: .psbt, options: defaultSplitOptions())
+    fatalError("should have thrown")
+} catch SplitError.Empty {
+}
+
+do {
+    _ = try joinParts(parts: ["B#HP0100AA"])
+    fatalError("should have thrown")
+} catch JoinError.HeaderParseError {
+}
+
+do {
+    _ = try ContinuousJoiner().addPart(part: "B$QP0200AA")
+    fatalError("should have thrown")
+} catch ContinuousJoinError.HeaderParseError {
+}
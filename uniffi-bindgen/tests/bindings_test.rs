@@ -0,0 +1,24 @@
+#![cfg(feature = "bindgen-tests")]
+
+// Runs the Kotlin and Swift scripts against bindings generated from the bbqr cdylib, run with:
+//   cargo test -p uniffi-bindgen --features bindgen-tests
+
+#[test]
+fn test_kotlin() {
+    uniffi::kotlin_test::run_test(
+        env!("CARGO_TARGET_TMPDIR"),
+        "bbqr",
+        "tests/bindings/test_bbqr.kts",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_swift() {
+    uniffi::swift_test::run_test(
+        env!("CARGO_TARGET_TMPDIR"),
+        "bbqr",
+        "tests/bindings/test_bbqr.swift",
+    )
+    .unwrap();
+}